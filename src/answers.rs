@@ -0,0 +1,88 @@
+use crate::parse::{lines, ParseError};
+use std::collections::BTreeMap;
+use std::fmt;
+
+/// Expected answers keyed by day and part.
+///
+/// The text form has one `<day> <part> <answer>` line per answer. Line breaks
+/// inside an answer are written as `\n` and backslashes as `\\`; lines starting
+/// with `#` are comments.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Answers {
+    answers: BTreeMap<(u8, u8), String>,
+}
+
+impl Answers {
+    pub fn parse(input: &str) -> Result<Answers, ParseError> {
+        let mut answers = BTreeMap::new();
+        for line in lines(input) {
+            if line.text.is_empty() || line.text.starts_with('#') {
+                continue;
+            }
+            let (day, rest) = line.split_once(" ")?;
+            let (part, answer) = rest.split_once(' ')
+                .ok_or_else(|| line.error(rest, "<part> <answer>"))?;
+            let key = (line.parse::<u8>(day, "a day")?, line.parse::<u8>(part, "a part")?);
+            answers.insert(key, unescape(answer).ok_or_else(|| line.error(answer, "an answer with \\n or \\\\ escapes"))?);
+        }
+        Ok(Answers { answers })
+    }
+
+    pub fn get(&self, day: u8, part: u8) -> Option<&str> {
+        self.answers.get(&(day, part)).map(|answer| answer.as_str())
+    }
+
+    pub fn insert(&mut self, day: u8, part: u8, answer: String) {
+        self.answers.insert((day, part), answer);
+    }
+}
+
+impl fmt::Display for Answers {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for ((day, part), answer) in &self.answers {
+            writeln!(f, "{day} {part} {}", answer.replace('\\', "\\\\").replace('\n', "\\n"))?;
+        }
+        Ok(())
+    }
+}
+
+fn unescape(answer: &str) -> Option<String> {
+    let mut result = String::new();
+    let mut chars = answer.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => match chars.next()? {
+                'n' => result.push('\n'),
+                '\\' => result.push('\\'),
+                _ => return None,
+            },
+            c => result.push(c),
+        }
+    }
+    Some(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip() {
+        let mut answers = Answers::default();
+        answers.insert(10, 2, "#..\n.#\\\n".to_string());
+        answers.insert(1, 1, "70764".to_string());
+        let text = answers.to_string();
+        assert_eq!(text, "1 1 70764\n10 2 #..\\n.#\\\\\\n\n");
+        assert_eq!(Answers::parse(&text).unwrap(), answers);
+    }
+
+    #[test]
+    fn test_parse() {
+        let answers = Answers::parse("# expected answers\n5 1 CMZ\n\n25 1 2=-1=0").unwrap();
+        assert_eq!(answers.get(5, 1), Some("CMZ"));
+        assert_eq!(answers.get(25, 1), Some("2=-1=0"));
+        assert_eq!(answers.get(5, 2), None);
+        let error = Answers::parse("5 x CMZ").unwrap_err();
+        assert_eq!((error.line, error.column, error.token.as_str()), (1, 3, "x"));
+    }
+}
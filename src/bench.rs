@@ -0,0 +1,107 @@
+use crate::{Input, Solution};
+use anyhow::{bail, Result};
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+/// Wall-clock durations of one phase over every repetition.
+#[derive(Debug, Clone)]
+pub struct Samples {
+    pub phase: String,
+    durations: Vec<Duration>,
+}
+
+impl Samples {
+    fn new(phase: &str) -> Samples {
+        Samples { phase: phase.to_string(), durations: Vec::new() }
+    }
+
+    fn record<T>(&mut self, f: impl FnOnce() -> Result<T>) -> Result<T> {
+        let start = Instant::now();
+        let result = black_box(f()?);
+        self.durations.push(start.elapsed());
+        Ok(result)
+    }
+
+    pub fn len(&self) -> usize {
+        self.durations.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.durations.is_empty()
+    }
+
+    pub fn min(&self) -> Duration {
+        self.durations.iter().copied().min().unwrap_or_default()
+    }
+
+    pub fn max(&self) -> Duration {
+        self.durations.iter().copied().max().unwrap_or_default()
+    }
+
+    pub fn median(&self) -> Duration {
+        let mut sorted = self.durations.clone();
+        sorted.sort();
+        match sorted.len() {
+            0 => Duration::ZERO,
+            n if n % 2 == 1 => sorted[n / 2],
+            n => (sorted[n / 2 - 1] + sorted[n / 2]) / 2,
+        }
+    }
+}
+
+/// The timings of one day: its parse phase followed by one entry per part.
+#[derive(Debug, Clone)]
+pub struct Timings {
+    pub day: u8,
+    pub phases: Vec<Samples>,
+}
+
+/// Parses `input` and answers each of `parts` `repeat` times, timing every phase separately.
+pub fn measure<S: Solution>(input: Input, parts: &[u8], repeat: usize) -> Result<Timings> {
+    if repeat == 0 {
+        bail!("repeat count must be at least 1");
+    }
+    let mut parse = Samples::new("parse");
+    let mut answers: Vec<Samples> = parts.iter().map(|part| Samples::new(&format!("part{part}"))).collect();
+    for _ in 0..repeat {
+        let parsed = parse.record(|| match input {
+            Input::Text(text) => S::parse(text),
+            Input::Example => S::example(),
+        })?;
+        for (samples, part) in answers.iter_mut().zip(parts) {
+            match part {
+                1 => { samples.record(|| S::part1(&parsed))?; },
+                2 if S::PARTS > 1 => { samples.record(|| S::part2(&parsed))?; },
+                _ => bail!("day {} has no part {part}", S::DAY),
+            }
+        }
+    }
+    let mut phases = vec![parse];
+    phases.extend(answers);
+    Ok(Timings { day: S::DAY, phases })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_statistics() {
+        let mut samples = Samples::new("parse");
+        for millis in [5, 1, 3, 2] {
+            samples.durations.push(Duration::from_millis(millis));
+        }
+        assert_eq!(samples.min(), Duration::from_millis(1));
+        assert_eq!(samples.max(), Duration::from_millis(5));
+        assert_eq!(samples.median(), Duration::from_micros(2500));
+    }
+
+    #[test]
+    fn test_measure() {
+        let timings = measure::<crate::day25::Day25>(Input::Example, &[1], 3).unwrap();
+        assert_eq!(timings.day, 25);
+        let phases: Vec<&str> = timings.phases.iter().map(|samples| samples.phase.as_str()).collect();
+        assert_eq!(phases, vec!["parse", "part1"]);
+        assert!(timings.phases.iter().all(|samples| samples.len() == 3));
+    }
+}
@@ -1,14 +1,79 @@
 use anyhow::Result;
-use std::fs;
+use crate::parse::blocks;
+use crate::Solution;
 
-fn main() -> Result<()> {
-    let mut calories: Vec<u32> = fs::read_to_string("day1.input")?
-      .split("\n\n")
-      .map(|lines| lines.split("\n").flat_map(|line| line.parse::<u32>()).sum())
-      .collect();
+pub struct Day1;
+
+impl Solution for Day1 {
+    const DAY: u8 = 1;
+    const EXAMPLE: Option<&'static str> = Some(EXAMPLE);
+    type Input = Vec<u32>;
+    type Part1 = u32;
+    type Part2 = u32;
+
+    fn parse(input: &str) -> Result<Self::Input> {
+        parse_input(input)
+    }
+
+    fn part1(calories: &Self::Input) -> Result<Self::Part1> {
+        Ok(part1(calories))
+    }
+
+    fn part2(calories: &Self::Input) -> Result<Self::Part2> {
+        Ok(part2(calories))
+    }
+}
+
+fn parse_input(input: &str) -> Result<Vec<u32>> {
+    let mut calories: Vec<u32> = Vec::new();
+    for elf in blocks(input) {
+        let mut total = 0;
+        for line in elf {
+            total += line.parse::<u32>(line.text, "calories")?;
+        }
+        calories.push(total);
+    }
     calories.sort();
     calories.reverse();
-    println!("{}", calories.iter().take(1).sum::<u32>());
-    println!("{}", calories.iter().take(3).sum::<u32>());
-    Ok(())
-}
\ No newline at end of file
+    Ok(calories)
+}
+
+fn part1(calories: &[u32]) -> u32 {
+    calories.iter().take(1).sum::<u32>()
+}
+
+fn part2(calories: &[u32]) -> u32 {
+    calories.iter().take(3).sum::<u32>()
+}
+
+const EXAMPLE: &str = "1000\n\
+                       2000\n\
+                       3000\n\
+                       \n\
+                       4000\n\
+                       \n\
+                       5000\n\
+                       6000\n\
+                       \n\
+                       7000\n\
+                       8000\n\
+                       9000\n\
+                       \n\
+                       10000";
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test1() {
+        let result = part1(&parse_input(EXAMPLE).unwrap());
+        assert_eq!(result, 24000);
+    }
+
+    #[test]
+    fn test2() {
+        let result = part2(&parse_input(EXAMPLE).unwrap());
+        assert_eq!(result, 45000);
+    }
+}
@@ -1,71 +1,89 @@
-use anyhow::Result;
-use std::fs;
+use anyhow::{anyhow, Result};
+use crate::Solution;
+use crate::parse::{lines, Line, ParseError};
 
 #[derive(Debug)]
-enum Instruction {
+pub enum Instruction {
     Noop,
     AddX(i32),
 }
 
-fn main() -> Result<()> {
-    let instructions: Vec<Instruction> = parse_input(fs::read_to_string("day10.input")?.as_str());
-    println!("{}", part1(&instructions));
-    println!("{}", part2(&instructions));
-    Ok(())
+pub struct Day10;
+
+impl Solution for Day10 {
+    const DAY: u8 = 10;
+    const EXAMPLE: Option<&'static str> = Some(EXAMPLE);
+    type Input = Vec<Instruction>;
+    type Part1 = i32;
+    type Part2 = String;
+
+    fn parse(input: &str) -> Result<Self::Input> {
+        parse_input(input)
+    }
+
+    fn part1(instructions: &Self::Input) -> Result<Self::Part1> {
+        part1(instructions)
+    }
+
+    fn part2(instructions: &Self::Input) -> Result<Self::Part2> {
+        Ok(part2(instructions))
+    }
 }
 
-fn parse_input(input: &str) -> Vec<Instruction> {
-    input.split("\n")
-      .map(|line| parse_line(line))
-      .collect()
+fn parse_input(input: &str) -> Result<Vec<Instruction>> {
+    Ok(lines(input).iter()
+      .map(parse_line)
+      .collect::<Result<Vec<Instruction>, _>>()?)
 }
 
-fn parse_line(line: &str) -> Instruction {
-    match line.split_once(" ") {
-        Some((_, x)) => Instruction::AddX(x.parse::<i32>().unwrap()),
-        None => Instruction::Noop,
+fn parse_line(line: &Line) -> Result<Instruction, ParseError> {
+    match line.text.split_once(" ") {
+        Some(("addx", x)) => Ok(Instruction::AddX(line.parse::<i32>(x, "an integer")?)),
+        None if line.text == "noop" => Ok(Instruction::Noop),
+        _ => Err(line.error(line.text, "\"noop\" or \"addx <integer>\"")),
     }
 }
 
-fn part1(instructions: &Vec<Instruction>) -> i32 {
+fn part1(instructions: &[Instruction]) -> Result<i32> {
     let register: Vec<i32> = instructions.iter()
       .fold(
         vec![1], 
         |mut acc, instruction| {
             match instruction {
                 Instruction::AddX(x) => {
-                    let last = acc.last().unwrap().clone();
+                    let last = *acc.last().unwrap();
                     acc.push(last);
                     acc.push(last + x);
                     acc
                 },
-                Instruction::Noop => {acc.push(acc.last().unwrap().clone()); acc}
+                Instruction::Noop => {acc.push(*acc.last().unwrap()); acc}
             }
         });
     let position: Vec<usize> = vec![20, 60, 100, 140, 180, 220];
-    let signal_strenth: i32 = position.iter()
-      .map(|x| x.clone())
-      .map(|x| (x as i32) * register[x - 1])
-      .sum();
-    signal_strenth
+    position.iter().copied()
+      .map(|x| register.get(x - 1)
+        .map(|value| (x as i32) * value)
+        .ok_or_else(|| anyhow!("the program ends after {} cycles, before cycle {x}", register.len() - 1)))
+      .sum()
 }
 
-fn part2(instructions: &Vec<Instruction>) -> String {
+fn part2(instructions: &[Instruction]) -> String {
     let register: Vec<i32> = instructions.iter()
     .fold(
       vec![1], 
       |mut acc, instruction| {
           match instruction {
               Instruction::AddX(x) => {
-                  let last = acc.last().unwrap().clone();
+                  let last = *acc.last().unwrap();
                   acc.push(last);
                   acc.push(last + x);
                   acc
               },
-              Instruction::Noop => {acc.push(acc.last().unwrap().clone()); acc}
+              Instruction::Noop => {acc.push(*acc.last().unwrap()); acc}
           }
       });
-    let result: String = register.iter()
+    // the last value is the register after the final cycle, which is never drawn
+    let result: String = register[..register.len() - 1].iter()
       .enumerate()
       .map(|(index, value)| ((index % 40) as i32, value))
       .map(|(position, value)| {
@@ -86,168 +104,172 @@ fn part2(instructions: &Vec<Instruction>) -> String {
 }
 
 
-// cargo test --bin day10 -- --nocapture
+const EXAMPLE: &str = "addx 15\n\
+                       addx -11\n\
+                       addx 6\n\
+                       addx -3\n\
+                       addx 5\n\
+                       addx -1\n\
+                       addx -8\n\
+                       addx 13\n\
+                       addx 4\n\
+                       noop\n\
+                       addx -1\n\
+                       addx 5\n\
+                       addx -1\n\
+                       addx 5\n\
+                       addx -1\n\
+                       addx 5\n\
+                       addx -1\n\
+                       addx 5\n\
+                       addx -1\n\
+                       addx -35\n\
+                       addx 1\n\
+                       addx 24\n\
+                       addx -19\n\
+                       addx 1\n\
+                       addx 16\n\
+                       addx -11\n\
+                       noop\n\
+                       noop\n\
+                       addx 21\n\
+                       addx -15\n\
+                       noop\n\
+                       noop\n\
+                       addx -3\n\
+                       addx 9\n\
+                       addx 1\n\
+                       addx -3\n\
+                       addx 8\n\
+                       addx 1\n\
+                       addx 5\n\
+                       noop\n\
+                       noop\n\
+                       noop\n\
+                       noop\n\
+                       noop\n\
+                       addx -36\n\
+                       noop\n\
+                       addx 1\n\
+                       addx 7\n\
+                       noop\n\
+                       noop\n\
+                       noop\n\
+                       addx 2\n\
+                       addx 6\n\
+                       noop\n\
+                       noop\n\
+                       noop\n\
+                       noop\n\
+                       noop\n\
+                       addx 1\n\
+                       noop\n\
+                       noop\n\
+                       addx 7\n\
+                       addx 1\n\
+                       noop\n\
+                       addx -13\n\
+                       addx 13\n\
+                       addx 7\n\
+                       noop\n\
+                       addx 1\n\
+                       addx -33\n\
+                       noop\n\
+                       noop\n\
+                       noop\n\
+                       addx 2\n\
+                       noop\n\
+                       noop\n\
+                       noop\n\
+                       addx 8\n\
+                       noop\n\
+                       addx -1\n\
+                       addx 2\n\
+                       addx 1\n\
+                       noop\n\
+                       addx 17\n\
+                       addx -9\n\
+                       addx 1\n\
+                       addx 1\n\
+                       addx -3\n\
+                       addx 11\n\
+                       noop\n\
+                       noop\n\
+                       addx 1\n\
+                       noop\n\
+                       addx 1\n\
+                       noop\n\
+                       noop\n\
+                       addx -13\n\
+                       addx -19\n\
+                       addx 1\n\
+                       addx 3\n\
+                       addx 26\n\
+                       addx -30\n\
+                       addx 12\n\
+                       addx -1\n\
+                       addx 3\n\
+                       addx 1\n\
+                       noop\n\
+                       noop\n\
+                       noop\n\
+                       addx -9\n\
+                       addx 18\n\
+                       addx 1\n\
+                       addx 2\n\
+                       noop\n\
+                       noop\n\
+                       addx 9\n\
+                       noop\n\
+                       noop\n\
+                       noop\n\
+                       addx -1\n\
+                       addx 2\n\
+                       addx -37\n\
+                       addx 1\n\
+                       addx 3\n\
+                       noop\n\
+                       addx 15\n\
+                       addx -21\n\
+                       addx 22\n\
+                       addx -6\n\
+                       addx 1\n\
+                       noop\n\
+                       addx 2\n\
+                       addx 1\n\
+                       noop\n\
+                       addx -10\n\
+                       noop\n\
+                       noop\n\
+                       addx 20\n\
+                       addx 1\n\
+                       addx 2\n\
+                       addx 2\n\
+                       addx -6\n\
+                       addx -11\n\
+                       noop\n\
+                       noop\n\
+                       noop";
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
     #[test]
     fn test1() {
-        let result = part1(&parse_input(INPUT));
-        assert_eq!(result, 0);
+        let result = part1(&parse_input(EXAMPLE).unwrap()).unwrap();
+        assert_eq!(result, 13140);
+        assert!(part1(&parse_input("noop\naddx 3").unwrap()).is_err());
     }
 
     #[test]
     fn test2() {
-        let result = part2(&parse_input(INPUT));
-        println!("{result}");
-        assert_eq!(result, "");
+        let result = part2(&parse_input(EXAMPLE).unwrap());
+        assert_eq!(result, "##..##..##..##..##..##..##..##..##..##..\n\
+                            ###...###...###...###...###...###...###.\n\
+                            ####....####....####....####....####....\n\
+                            #####.....#####.....#####.....#####.....\n\
+                            ######......######......######......####\n\
+                            #######.......#######.......#######.....\n");
     }
-
-    const INPUT: &str = "addx 15\n\
-                        addx -11\n\
-                        addx 6\n\
-                        addx -3\n\
-                        addx 5\n\
-                        addx -1\n\
-                        addx -8\n\
-                        addx 13\n\
-                        addx 4\n\
-                        noop\n\
-                        addx -1\n\
-                        addx 5\n\
-                        addx -1\n\
-                        addx 5\n\
-                        addx -1\n\
-                        addx 5\n\
-                        addx -1\n\
-                        addx 5\n\
-                        addx -1\n\
-                        addx -35\n\
-                        addx 1\n\
-                        addx 24\n\
-                        addx -19\n\
-                        addx 1\n\
-                        addx 16\n\
-                        addx -11\n\
-                        noop\n\
-                        noop\n\
-                        addx 21\n\
-                        addx -15\n\
-                        noop\n\
-                        noop\n\
-                        addx -3\n\
-                        addx 9\n\
-                        addx 1\n\
-                        addx -3\n\
-                        addx 8\n\
-                        addx 1\n\
-                        addx 5\n\
-                        noop\n\
-                        noop\n\
-                        noop\n\
-                        noop\n\
-                        noop\n\
-                        addx -36\n\
-                        noop\n\
-                        addx 1\n\
-                        addx 7\n\
-                        noop\n\
-                        noop\n\
-                        noop\n\
-                        addx 2\n\
-                        addx 6\n\
-                        noop\n\
-                        noop\n\
-                        noop\n\
-                        noop\n\
-                        noop\n\
-                        addx 1\n\
-                        noop\n\
-                        noop\n\
-                        addx 7\n\
-                        addx 1\n\
-                        noop\n\
-                        addx -13\n\
-                        addx 13\n\
-                        addx 7\n\
-                        noop\n\
-                        addx 1\n\
-                        addx -33\n\
-                        noop\n\
-                        noop\n\
-                        noop\n\
-                        addx 2\n\
-                        noop\n\
-                        noop\n\
-                        noop\n\
-                        addx 8\n\
-                        noop\n\
-                        addx -1\n\
-                        addx 2\n\
-                        addx 1\n\
-                        noop\n\
-                        addx 17\n\
-                        addx -9\n\
-                        addx 1\n\
-                        addx 1\n\
-                        addx -3\n\
-                        addx 11\n\
-                        noop\n\
-                        noop\n\
-                        addx 1\n\
-                        noop\n\
-                        addx 1\n\
-                        noop\n\
-                        noop\n\
-                        addx -13\n\
-                        addx -19\n\
-                        addx 1\n\
-                        addx 3\n\
-                        addx 26\n\
-                        addx -30\n\
-                        addx 12\n\
-                        addx -1\n\
-                        addx 3\n\
-                        addx 1\n\
-                        noop\n\
-                        noop\n\
-                        noop\n\
-                        addx -9\n\
-                        addx 18\n\
-                        addx 1\n\
-                        addx 2\n\
-                        noop\n\
-                        noop\n\
-                        addx 9\n\
-                        noop\n\
-                        noop\n\
-                        noop\n\
-                        addx -1\n\
-                        addx 2\n\
-                        addx -37\n\
-                        addx 1\n\
-                        addx 3\n\
-                        noop\n\
-                        addx 15\n\
-                        addx -21\n\
-                        addx 22\n\
-                        addx -6\n\
-                        addx 1\n\
-                        noop\n\
-                        addx 2\n\
-                        addx 1\n\
-                        noop\n\
-                        addx -10\n\
-                        noop\n\
-                        noop\n\
-                        addx 20\n\
-                        addx 1\n\
-                        addx 2\n\
-                        addx 2\n\
-                        addx -6\n\
-                        addx -11\n\
-                        noop\n\
-                        noop\n\
-                        noop";
 }
\ No newline at end of file
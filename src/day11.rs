@@ -1,159 +1,403 @@
-use anyhow::Result;
-use std::fs;
+use anyhow::{anyhow, Result};
+use crate::Solution;
+use crate::parse::{blocks, Line, ParseError};
+use num::integer::{lcm, Integer};
+use num::traits::{CheckedAdd, CheckedMul, CheckedSub};
+use std::fmt::Display;
 
 #[derive(Debug)]
-struct Monkey {
+pub struct Monkey {
     items: Vec<i64>,
-    operation: fn(i64) -> i64,
+    operation: Expression,
     divisor: i64,
     true_index: usize,
     false_index: usize,
 }
 
-impl Monkey {
-    fn of(items: Vec<i64>, operation: fn(i64) -> i64, divisor: i64, true_index: usize, false_index: usize) -> Monkey {
-        Monkey { items, operation, divisor, true_index, false_index }
-    }
-}
-
-fn main() -> Result<()> {
-    let monkeys = parse_input(fs::read_to_string("day11.input")?.as_str());
-    // let monkeys = vec![
-    //     Monkey::of(Vec::from([98, 89, 52]), |x| x * 2, 5, 6, 1),
-    //     Monkey::of(Vec::from([57, 95, 80, 92, 57, 78]), |x| x * 13, 2, 2, 6),
-    //     Monkey::of(Vec::from([82, 74, 97, 75, 51, 92, 83]), |x| x + 5, 19, 7, 5),
-    //     Monkey::of(Vec::from([97, 88, 51, 68, 76]), |x| x + 6, 7, 0, 4),
-    //     Monkey::of(Vec::from([63]), |x| x + 1, 17, 0, 1),
-    //     Monkey::of(Vec::from([94, 91, 51, 63]), |x| x + 4, 13, 4, 3),
-    //     Monkey::of(Vec::from([61, 54, 94, 71, 74, 68, 98, 83]), |x| x + 2, 3, 2, 7),
-    //     Monkey::of(Vec::from([90, 56]), |x| x * x, 11, 3, 5),
-    // ];
-    println!("{}", part1(&monkeys));
-    println!("{}", part2(&monkeys));
-    Ok(())
-}
-
-fn parse_input(input: &str) -> Vec<Monkey> {
-    input.split("\n\n")
-    .map(|line| parse_monkey(line))
-    .collect()
-}
-
-fn parse_monkey(money_string: &str) -> Monkey {
-    let lines: Vec<&str> = money_string.split("\n").collect();
-    let items: Vec<i64> = lines[1].strip_prefix("  Starting items: ").unwrap().split(", ").map(|x| x.parse::<i64>().unwrap()).collect();
-    let operation = parse_operation(lines[2].strip_prefix("  Operation: new = ").unwrap());
-    let divisor = lines[3].strip_prefix("  Test: divisible by ").unwrap().parse::<i64>().unwrap();
-    let true_index = lines[4].strip_prefix("    If true: throw to monkey ").unwrap().parse::<usize>().unwrap();
-    let false_index = lines[5].strip_prefix("    If false: throw to monkey ").unwrap().parse::<usize>().unwrap();
-    Monkey::of(items, operation, divisor, true_index, false_index)
-}
-
-fn parse_operation(s: &str) -> fn(i64) -> i64 {
-    println!("{s}");
-    let mut iter = s.splitn(3, " ");
-    iter.next();
-    let operator = iter.next().unwrap();
-    let right = iter.next().unwrap();
-    match (operator, right) {
-        ("+", _) => {let r = right.parse::<i64>().unwrap(); |x| x + r},
-        ("*", _) => {let r = right.parse::<i64>().unwrap(); |x| x * r},
-        ("*", "old") => |x| x * x,
-        _ => panic!(),
-    }
-}
-
-fn part1(monkeys: &Vec<Monkey>) -> i64 {
-    helper(monkeys, 20, &|x| x / 3)
-}
-
-fn part2(monkeys: &Vec<Monkey>) -> i64 {
-    let modulus: i64 = monkeys.iter().map(|monkey| monkey.divisor).product();
-    helper(monkeys, 10000, &|x| x % modulus)
-}
-
-fn helper(monkeys: &Vec<Monkey>, round: u32, relief_function: &dyn Fn(i64) -> i64) -> i64 {
-    let number_of_moneys = monkeys.len();
-    let mut current_round_items: Vec<Vec<i64>> = monkeys.iter().map(|monkey| monkey.items.clone()).collect();
-    let mut inspected_counts = vec![0; number_of_moneys];
-    for _ in 0..round {
-        for monkey_index in 0..number_of_moneys {
-            let mut next_round_items = current_round_items.clone();
-            next_round_items[monkey_index] = Vec::new();
-            let items_of_current_monkey = &current_round_items[monkey_index];
-            inspected_counts[monkey_index] += items_of_current_monkey.len();
-            for item in items_of_current_monkey {
-                let new_item = relief_function((monkeys[monkey_index].operation)(*item));
-                if new_item % monkeys[monkey_index].divisor == 0 {
-                    next_round_items[monkeys[monkey_index].true_index].push(new_item);
-                } else {
-                    next_round_items[monkeys[monkey_index].false_index].push(new_item);
-                }
+/// The right hand side of a monkey's `new = ...` operation.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Expression {
+    Old,
+    Number(i64),
+    Add(Box<Expression>, Box<Expression>),
+    Subtract(Box<Expression>, Box<Expression>),
+    Multiply(Box<Expression>, Box<Expression>),
+}
+
+impl Expression {
+    /// The new worry level, or `None` if it does not fit in `W`.
+    pub fn eval<W: Worry>(&self, old: &W) -> Option<W> {
+        match self {
+            Expression::Old => Some(old.clone()),
+            Expression::Number(n) => Some(W::from(*n)),
+            Expression::Add(left, right) => left.eval(old)?.checked_add(&right.eval(old)?),
+            Expression::Subtract(left, right) => left.eval(old)?.checked_sub(&right.eval(old)?),
+            Expression::Multiply(left, right) => left.eval(old)?.checked_mul(&right.eval(old)?),
+        }
+    }
+}
+
+/// A type to keep worry levels in: `i64`, or `num::BigInt` for exact values that never overflow.
+pub trait Worry: Integer + Clone + Display + CheckedAdd + CheckedSub + CheckedMul + From<i64> {}
+
+impl<W: Integer + Clone + Display + CheckedAdd + CheckedSub + CheckedMul + From<i64>> Worry for W {}
+
+/// How worry levels drop after a monkey has inspected an item.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Relief {
+    /// Divided by three and rounded down, as long as the items are not damaged.
+    DivideBy3,
+    /// Kept modulo the least common multiple of all divisors, which no monkey can tell apart.
+    ModuloLcm,
+    None,
+}
+
+/// An item thrown from one monkey to another, with its worry level after relief.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Throw<W = i64> {
+    pub from: usize,
+    pub to: usize,
+    pub worry: W,
+}
+
+/// The monkeys playing keep away, one round at a time.
+pub struct Simulation<'a, W = i64> {
+    monkeys: &'a [Monkey],
+    relief: Relief,
+    modulus: W,
+    round: usize,
+    items: Vec<Vec<W>>,
+    inspections: Vec<usize>,
+}
+
+impl<'a, W: Worry> Simulation<'a, W> {
+    pub fn new(monkeys: &'a [Monkey], relief: Relief) -> Simulation<'a, W> {
+        Simulation {
+            monkeys,
+            relief,
+            modulus: W::from(monkeys.iter().fold(1, |modulus, monkey| lcm(modulus, monkey.divisor))),
+            round: 0,
+            items: monkeys.iter().map(|monkey| monkey.items.iter().map(|item| W::from(*item)).collect()).collect(),
+            inspections: vec![0; monkeys.len()],
+        }
+    }
+
+    /// The number of rounds played so far.
+    pub fn round(&self) -> usize {
+        self.round
+    }
+
+    /// The worry levels of the items each monkey holds, in the order it will inspect them.
+    pub fn items(&self) -> &[Vec<W>] {
+        &self.items
+    }
+
+    /// How many items each monkey has inspected so far.
+    pub fn inspections(&self) -> &[usize] {
+        &self.inspections
+    }
+
+    /// Plays one round and returns every throw in the order it happened.
+    pub fn step(&mut self) -> Result<Vec<Throw<W>>> {
+        self.round += 1;
+        let mut throws = Vec::new();
+        for (index, monkey) in self.monkeys.iter().enumerate() {
+            let items = std::mem::take(&mut self.items[index]);
+            self.inspections[index] += items.len();
+            for item in items {
+                let inspected = monkey.operation.eval(&item)
+                    .ok_or_else(|| anyhow!("monkey {index} overflows worry level {item} in round {}", self.round))?;
+                let worry = match self.relief {
+                    Relief::DivideBy3 => inspected / W::from(3),
+                    Relief::ModuloLcm => inspected % self.modulus.clone(),
+                    Relief::None => inspected,
+                };
+                let to = if worry.is_multiple_of(&W::from(monkey.divisor)) { monkey.true_index } else { monkey.false_index };
+                self.items[to].push(worry.clone());
+                throws.push(Throw { from: index, to, worry });
             }
-            current_round_items = next_round_items.clone();
         }
+        Ok(throws)
     }
-    inspected_counts.sort();
-    inspected_counts.reverse();
-    inspected_counts[0] as i64 * inspected_counts[1] as i64
+
+    pub fn run(&mut self, rounds: usize) -> Result<()> {
+        for _ in 0..rounds {
+            self.step()?;
+        }
+        Ok(())
+    }
+
+    /// The product of the two highest inspection counts.
+    pub fn monkey_business(&self) -> usize {
+        let mut counts = self.inspections.clone();
+        counts.sort_unstable_by(|a, b| b.cmp(a));
+        counts.iter().take(2).product()
+    }
+}
+
+pub struct Day11;
+
+impl Solution for Day11 {
+    const DAY: u8 = 11;
+    const EXAMPLE: Option<&'static str> = Some(EXAMPLE);
+    type Input = Vec<Monkey>;
+    type Part1 = usize;
+    type Part2 = usize;
+
+    fn parse(input: &str) -> Result<Self::Input> {
+        parse_input(input)
+    }
+
+    fn part1(monkeys: &Self::Input) -> Result<Self::Part1> {
+        part1(monkeys)
+    }
+
+    fn part2(monkeys: &Self::Input) -> Result<Self::Part2> {
+        part2(monkeys)
+    }
+}
+
+fn parse_input(input: &str) -> Result<Vec<Monkey>> {
+    let blocks = blocks(input);
+    let monkeys = blocks.iter()
+    .map(|block| parse_monkey(block))
+    .collect::<Result<Vec<Monkey>, _>>()?;
+    for (block, monkey) in blocks.iter().zip(&monkeys) {
+        for (line, index) in [(&block[4], monkey.true_index), (&block[5], monkey.false_index)] {
+            if index >= monkeys.len() {
+                let (_, token) = line.text.rsplit_once(' ').unwrap();
+                return Err(line.error(token, &format!("a monkey below {}", monkeys.len())).into());
+            }
+        }
+    }
+    Ok(monkeys)
 }
 
+fn parse_monkey(lines: &[Line]) -> Result<Monkey, ParseError> {
+    let last: &Line = lines.last().expect("blocks are never empty");
+    if lines.len() != 6 {
+        return Err(last.error_at(last.text.len() + 1, "", "six lines per monkey"));
+    }
+    lines[0].strip_prefix("Monkey ")?;
+    let items: Vec<i64> = lines[1].strip_prefix("  Starting items: ")?
+      .split(", ")
+      .map(|x| lines[1].parse::<i64>(x, "a worry level"))
+      .collect::<Result<Vec<i64>, _>>()?;
+    let operation = parse_operation(&lines[2], lines[2].strip_prefix("  Operation: new = ")?)?;
+    let divisor = lines[3].parse::<i64>(lines[3].strip_prefix("  Test: divisible by ")?, "a divisor")?;
+    if divisor <= 0 {
+        return Err(lines[3].error(lines[3].strip_prefix("  Test: divisible by ")?, "a positive divisor"));
+    }
+    let true_index = lines[4].parse::<usize>(lines[4].strip_prefix("    If true: throw to monkey ")?, "a monkey index")?;
+    let false_index = lines[5].parse::<usize>(lines[5].strip_prefix("    If false: throw to monkey ")?, "a monkey index")?;
+    Ok(Monkey { items, operation, divisor, true_index, false_index })
+}
+
+/// Parses `expression` from `line`, with `*` binding tighter than `+` and `-`.
+fn parse_operation(line: &Line, expression: &str) -> Result<Expression, ParseError> {
+    let mut parser = Parser { line, tokens: tokenize(expression), position: 0 };
+    let result = parser.sum()?;
+    match parser.tokens.get(parser.position) {
+        Some(token) => Err(line.error(token, "an operator")),
+        None => Ok(result),
+    }
+}
+
+/// Splits an expression into numbers, names, operators and parentheses, skipping spaces.
+fn tokenize(expression: &str) -> Vec<&str> {
+    let mut tokens = Vec::new();
+    let mut rest = expression.trim_start();
+    while let Some(c) = rest.chars().next() {
+        let length = match c {
+            '0'..='9' => rest.find(|c: char| !c.is_ascii_digit()).unwrap_or(rest.len()),
+            'a'..='z' => rest.find(|c: char| !c.is_ascii_lowercase()).unwrap_or(rest.len()),
+            _ => c.len_utf8(),
+        };
+        tokens.push(&rest[..length]);
+        rest = rest[length..].trim_start();
+    }
+    tokens
+}
+
+struct Parser<'a> {
+    line: &'a Line<'a>,
+    tokens: Vec<&'a str>,
+    position: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn next(&mut self, expected: &str) -> Result<&'a str, ParseError> {
+        let token = self.tokens.get(self.position)
+            .ok_or_else(|| self.line.error_at(self.line.text.chars().count() + 1, "", expected))?;
+        self.position += 1;
+        Ok(token)
+    }
+
+    fn peek(&self) -> Option<&'a str> {
+        self.tokens.get(self.position).copied()
+    }
+
+    fn sum(&mut self) -> Result<Expression, ParseError> {
+        let mut left = self.product()?;
+        while let Some(operator @ ("+" | "-")) = self.peek() {
+            self.position += 1;
+            let right = Box::new(self.product()?);
+            left = match operator {
+                "+" => Expression::Add(Box::new(left), right),
+                _ => Expression::Subtract(Box::new(left), right),
+            };
+        }
+        Ok(left)
+    }
+
+    fn product(&mut self) -> Result<Expression, ParseError> {
+        let mut left = self.operand()?;
+        while let Some("*") = self.peek() {
+            self.position += 1;
+            left = Expression::Multiply(Box::new(left), Box::new(self.operand()?));
+        }
+        Ok(left)
+    }
+
+    fn operand(&mut self) -> Result<Expression, ParseError> {
+        let token = self.next("an operand")?;
+        match token {
+            "old" => Ok(Expression::Old),
+            "(" => {
+                let inner = self.sum()?;
+                match self.next("\")\"")? {
+                    ")" => Ok(inner),
+                    other => Err(self.line.error(other, "\")\"")),
+                }
+            },
+            _ => self.line.parse::<i64>(token, "\"old\", an integer or \"(\"").map(Expression::Number),
+        }
+    }
+}
+
+fn part1(monkeys: &[Monkey]) -> Result<usize> {
+    let mut simulation: Simulation = Simulation::new(monkeys, Relief::DivideBy3);
+    simulation.run(20)?;
+    Ok(simulation.monkey_business())
+}
+
+fn part2(monkeys: &[Monkey]) -> Result<usize> {
+    let mut simulation: Simulation = Simulation::new(monkeys, Relief::ModuloLcm);
+    simulation.run(10000)?;
+    Ok(simulation.monkey_business())
+}
+
+const EXAMPLE: &str = r#"Monkey 0:
+  Starting items: 79, 98
+  Operation: new = old * 19
+  Test: divisible by 23
+    If true: throw to monkey 2
+    If false: throw to monkey 3
+
+Monkey 1:
+  Starting items: 54, 65, 75, 74
+  Operation: new = old + 6
+  Test: divisible by 19
+    If true: throw to monkey 2
+    If false: throw to monkey 0
+
+Monkey 2:
+  Starting items: 79, 60, 97
+  Operation: new = old * old
+  Test: divisible by 13
+    If true: throw to monkey 1
+    If false: throw to monkey 3
+
+Monkey 3:
+  Starting items: 74
+  Operation: new = old + 3
+  Test: divisible by 17
+    If true: throw to monkey 0
+    If false: throw to monkey 1"#;
 
-// cargo test --bin day10 -- --nocapture
 #[cfg(test)]
 mod tests {
     use super::*;
+    use num::BigInt;
 
-    #[ignore]
     #[test]
     fn test1() {
-        let monkeys = vec![
-            Monkey::of(Vec::from([79, 98]), |x| x * 19, 23, 2, 3),
-            Monkey::of(Vec::from([54, 65, 75, 74]), |x| x + 6, 19, 2, 0),
-            Monkey::of(Vec::from([79, 60, 97]), |x| x * x, 13, 1, 3),
-            Monkey::of(Vec::from([74]), |x| x + 3, 17, 0, 1),
-        ];
-        let result = part1(&monkeys);
+        let result = part1(&parse_input(EXAMPLE).unwrap()).unwrap();
         assert_eq!(result, 10605);
     }
 
     #[test]
     fn test2() {
-        let monkeys = vec![
-            Monkey::of(Vec::from([79, 98]), |x| x * 19, 23, 2, 3),
-            Monkey::of(Vec::from([54, 65, 75, 74]), |x| x + 6, 19, 2, 0),
-            Monkey::of(Vec::from([79, 60, 97]), |x| x * x, 13, 1, 3),
-            Monkey::of(Vec::from([74]), |x| x + 3, 17, 0, 1),
-        ];
-        let result = part2(&monkeys);
+        let result = part2(&parse_input(EXAMPLE).unwrap()).unwrap();
         assert_eq!(result, 2713310158);
     }
 
-    const INPUT: &str = "Monkey 0:\n\
-                        Starting items: 79, 98\n\
-                        Operation: new = old * 19\n\
-                        Test: divisible by 23\n\
-                        If true: throw to monkey 2\n\
-                        If false: throw to monkey 3\n\
-                        \n\
-                        Monkey 1:\n\
-                            Starting items: 54, 65, 75, 74\n\
-                            Operation: new = old + 6\n\
-                            Test: divisible by 19\n\
-                            If true: throw to monkey 2\n\
-                            If false: throw to monkey 0\n\
-                        \n\
-                        Monkey 2:\n\
-                            Starting items: 79, 60, 97\n\
-                            Operation: new = old * old\n\
-                            Test: divisible by 13\n\
-                            If true: throw to monkey 1\n\
-                            If false: throw to monkey 3\n\
-                        \n\
-                        Monkey 3:\n\
-                            Starting items: 74\n\
-                            Operation: new = old + 3\n\
-                            Test: divisible by 17\n\
-                            If true: throw to monkey 0\n\
-                            If false: throw to monkey 1";
+    #[test]
+    fn test_operation() {
+        let monkeys = parse_input(EXAMPLE).unwrap();
+        let operations: Vec<i64> = monkeys.iter().map(|monkey| monkey.operation.eval(&5i64).unwrap()).collect();
+        assert_eq!(operations, vec![95, 11, 25, 8]);
+
+        let line = Line { number: 3, text: "  Operation: new = old + old * (2 - old)" };
+        let expression = parse_operation(&line, &line.text[19..]).unwrap();
+        assert_eq!(expression.eval(&5i64), Some(-10));
+        let square = Expression::Multiply(Box::new(Expression::Old), Box::new(Expression::Old));
+        assert_eq!(square.eval(&(i64::MAX / 2)), None);
+        assert_eq!(square.eval(&BigInt::from(i64::MAX)), Some(BigInt::from(i64::MAX) * i64::MAX));
+        for (text, column, token) in [("old + old", 0, ""), ("old * old", 0, ""), ("old *", 6, ""), ("old * x", 7, "x"), ("old 2", 5, "2"), ("(old + 1", 9, "")] {
+            let line = Line { number: 1, text };
+            match parse_operation(&line, text) {
+                Ok(expression) => assert_eq!(column, 0, "{text} parsed as {expression:?}"),
+                Err(error) => assert_eq!((error.column, error.token.as_str()), (column, token), "{text}"),
+            }
+        }
+    }
+
+    #[test]
+    fn test_simulation() {
+        let monkeys = parse_input(EXAMPLE).unwrap();
+        let mut simulation: Simulation = Simulation::new(&monkeys, Relief::DivideBy3);
+        let throws = simulation.step().unwrap();
+        assert_eq!(throws[0], Throw { from: 0, to: 3, worry: 500 });
+        assert_eq!(throws.len(), 14);
+        assert_eq!(simulation.items(), &[vec![20, 23, 27, 26], vec![2080, 25, 167, 207, 401, 1046], vec![], vec![]]);
+        simulation.run(19).unwrap();
+        assert_eq!(simulation.round(), 20);
+        assert_eq!(simulation.inspections(), &[101, 95, 7, 105]);
+
+        let mut simulation: Simulation = Simulation::new(&monkeys, Relief::ModuloLcm);
+        assert_eq!(simulation.modulus, 96577);
+        simulation.run(20).unwrap();
+        assert_eq!(simulation.inspections(), &[99, 97, 8, 103]);
+
+        let mut simulation: Simulation = Simulation::new(&monkeys, Relief::None);
+        assert!(simulation.run(20).is_err());
+    }
+
+    #[test]
+    fn test_exact() {
+        // the reduced worry levels must send every item where the exact ones do
+        let monkeys = parse_input(EXAMPLE).unwrap();
+        let mut exact: Simulation<BigInt> = Simulation::new(&monkeys, Relief::None);
+        let mut reduced: Simulation = Simulation::new(&monkeys, Relief::ModuloLcm);
+        let modulus = BigInt::from(reduced.modulus);
+        for _ in 0..12 {
+            let exact_throws = exact.step().unwrap();
+            let reduced_throws = reduced.step().unwrap();
+            assert_eq!(exact_throws.len(), reduced_throws.len());
+            for (exact_throw, reduced_throw) in exact_throws.iter().zip(&reduced_throws) {
+                assert_eq!((exact_throw.from, exact_throw.to), (reduced_throw.from, reduced_throw.to));
+                assert_eq!(&exact_throw.worry % &modulus, BigInt::from(reduced_throw.worry));
+            }
+        }
+        assert_eq!(exact.inspections(), reduced.inspections());
+    }
+
+    #[test]
+    fn test_parse_error() {
+        let error = parse_input(&EXAMPLE.replace("throw to monkey 3", "throw to monkey 4")).unwrap_err().downcast::<ParseError>().unwrap();
+        assert_eq!((error.line, error.column, error.token.as_str()), (6, 31, "4"));
+        let error = parse_input(&EXAMPLE.replace("divisible by 19", "divisible by 0")).unwrap_err().downcast::<ParseError>().unwrap();
+        assert_eq!((error.line, error.column, error.token.as_str()), (11, 22, "0"));
+    }
 }
\ No newline at end of file
@@ -1,5 +1,6 @@
 use anyhow::Result;
-use std::fs;
+use crate::Solution;
+use crate::parse::{blocks, Line, ParseError};
 use serde_json::Value;
 use serde_json::Value::Number;
 use serde_json::Value::Array;
@@ -7,36 +8,75 @@ use serde_json::json;
 use std::cmp::Ordering;
 
 
-fn main() -> Result<()> {
-    let pairs: Vec<Value> = parse_input(fs::read_to_string("day13.input")?.as_str());
-    println!("{}", part1(&pairs));
-    println!("{}", part2(&pairs));
-    Ok(())
+pub struct Day13;
+
+impl Solution for Day13 {
+    const DAY: u8 = 13;
+    const EXAMPLE: Option<&'static str> = Some(EXAMPLE);
+    type Input = Vec<(Value, Value)>;
+    type Part1 = usize;
+    type Part2 = usize;
+
+    fn parse(input: &str) -> Result<Self::Input> {
+        parse_input(input)
+    }
+
+    fn part1(pairs: &Self::Input) -> Result<Self::Part1> {
+        Ok(part1(pairs))
+    }
+
+    fn part2(pairs: &Self::Input) -> Result<Self::Part2> {
+        Ok(part2(pairs))
+    }
+}
+
+fn parse_input(input: &str) -> Result<Vec<(Value, Value)>> {
+    let mut pairs = Vec::new();
+    for block in blocks(input) {
+        match block.as_slice() {
+            [left, right] => pairs.push((parse_packet(left)?, parse_packet(right)?)),
+            [single] => return Err(single.error(single.text, "a pair of packets on two lines").into()),
+            [_, _, third, ..] => return Err(third.error(third.text, "a blank line after each pair of packets").into()),
+            [] => unreachable!("blocks are never empty"),
+        }
+    }
+    Ok(pairs)
 }
 
-fn parse_input(input: &str) -> Vec<Value> {
-    input.split("\n")
-      .filter(|s| !s.is_empty())
-      .flat_map(|s| serde_json::from_str(s))
-      .collect()
+fn parse_packet(line: &Line) -> Result<Value, ParseError> {
+    let packet: Value = serde_json::from_str(line.text)
+      .map_err(|error| line.error_at(error.column(), line.text, "a packet"))?;
+    if is_packet(&packet) {
+        Ok(packet)
+    } else {
+        Err(line.error(line.text, "a list of integers and lists"))
+    }
 }
 
-fn part1(pairs: &Vec<Value>) -> usize {
-    pairs.chunks(2)
+fn is_packet(value: &Value) -> bool {
+    match value {
+        Number(x) => x.is_i64(),
+        Array(values) => values.iter().all(is_packet),
+        _ => false,
+    }
+}
+
+fn part1(pairs: &[(Value, Value)]) -> usize {
+    pairs.iter()
       .enumerate()
-      .map(|(index, pair)| (index, order(&pair[0], &pair[1])))
+      .map(|(index, (left, right))| (index, order(left, right)))
       .filter(|(_, order)| *order == Ordering::Less)
       .map(|(index, _)| index + 1)
       .sum()
 }
 
-fn part2(pairs: &Vec<Value>) -> usize {
-    let mut elements = pairs.clone();
+fn part2(pairs: &[(Value, Value)]) -> usize {
+    let mut elements: Vec<Value> = pairs.iter().flat_map(|(left, right)| [left.clone(), right.clone()]).collect();
     let key1: Value = Array(vec![Array(vec![json!(2)])]);
     let key2: Value = Array(vec![Array(vec![json!(6)])]);
     elements.push(key1.clone());
     elements.push(key2.clone());
-    elements.sort_by(|a, b| order(a, b));
+    elements.sort_by(order);
     elements.iter()
       .enumerate()
       .filter(|(_, element)| **element == key1 || **element == key2)
@@ -48,13 +88,13 @@ fn order(a: &Value, b: &Value) -> Ordering {
     match (a, b) {
         (Number(x), Number(y)) => x.as_i64().unwrap().cmp(&y.as_i64().unwrap()),
         (Array(vec_x), Array(vec_y)) => order_vec(vec_x, vec_y),
-        (x, Array(vec_y)) => order_vec(&vec![x.clone()], vec_y),
-        (Array(vec_x), y) => order_vec(vec_x, &vec![y.clone()]),
+        (x, Array(vec_y)) => order_vec(std::slice::from_ref(x), vec_y),
+        (Array(vec_x), y) => order_vec(vec_x, std::slice::from_ref(y)),
         _ => panic!(),
     }
 }
 
-fn order_vec(a: &Vec<Value>, b: &Vec<Value>) -> Ordering {
+fn order_vec(a: &[Value], b: &[Value]) -> Ordering {
     let mut it_xs = a.iter();
     let mut it_ys = b.iter();
     let mut result = Ordering::Equal;
@@ -72,44 +112,51 @@ fn order_vec(a: &Vec<Value>, b: &Vec<Value>) -> Ordering {
     result
 }
 
-// cargo test --bin day10 -- --nocapture
+const EXAMPLE: &str = "[1,1,3,1,1]\n\
+                       [1,1,5,1,1]\n\
+                       \n\
+                       [[1],[2,3,4]]\n\
+                       [[1],4]\n\
+                       \n\
+                       [9]\n\
+                       [[8,7,6]]\n\
+                       \n\
+                       [[4,4],4,4]\n\
+                       [[4,4],4,4,4]\n\
+                       \n\
+                       [7,7,7,7]\n\
+                       [7,7,7]\n\
+                       \n\
+                       []\n\
+                       [3]\n\
+                       \n\
+                       [[[]]]\n\
+                       [[]]\n\
+                       \n\
+                       [1,[2,[3,[4,[5,6,7]]]],8,9]\n\
+                       [1,[2,[3,[4,[5,6,0]]]],8,9]";
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
     #[test]
     fn test1() {
-        let result = part1(&parse_input(&INPUT));
+        let result = part1(&parse_input(EXAMPLE).unwrap());
         assert_eq!(result, 13);
     }
 
     #[test]
     fn test2() {
-        let result = part2(&parse_input(&INPUT));
+        let result = part2(&parse_input(EXAMPLE).unwrap());
         assert_eq!(result, 140);
     }
 
-    const INPUT: &str ="[1,1,3,1,1]\n\
-                        [1,1,5,1,1]\n\
-                        \n\
-                        [[1],[2,3,4]]\n\
-                        [[1],4]\n\
-                        \n\
-                        [9]\n\
-                        [[8,7,6]]\n\
-                        \n\
-                        [[4,4],4,4]\n\
-                        [[4,4],4,4,4]\n\
-                        \n\
-                        [7,7,7,7]\n\
-                        [7,7,7]\n\
-                        \n\
-                        []\n\
-                        [3]\n\
-                        \n\
-                        [[[]]]\n\
-                        [[]]\n\
-                        \n\
-                        [1,[2,[3,[4,[5,6,7]]]],8,9]\n\
-                        [1,[2,[3,[4,[5,6,0]]]],8,9]";
+    #[test]
+    fn test_pairs() {
+        let error = parse_input("[1]\n[2]\n\n[3]").unwrap_err().downcast::<ParseError>().unwrap();
+        assert_eq!(error.line, 4);
+        let error = parse_input("[1]\n[2]\n[3]").unwrap_err().downcast::<ParseError>().unwrap();
+        assert_eq!(error.line, 3);
+    }
 }
\ No newline at end of file
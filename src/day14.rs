@@ -1,5 +1,7 @@
-use anyhow::Result;
-use std::fs;
+use anyhow::{bail, Result};
+use crate::Solution;
+use crate::grid::Grid;
+use crate::parse::{lines, Line, ParseError};
 
 #[derive(Debug, Clone, PartialEq)]
 enum Material {
@@ -11,41 +13,68 @@ enum Material {
 
 #[derive(Debug, Clone)]
 struct Map {
-    map: Vec<Vec<Material>>,
+    map: Grid<Material>,
     entry: (usize, usize),
 }
 
-fn main() -> Result<()> {
-    let lines: Vec<Vec<(usize, usize)>> = parse_input(fs::read_to_string("day14.input")?.as_str());
-    println!("{}", part1(build_map_part1(&lines)));
-    println!("{}", part2(build_map_part2(&lines)));
-    Ok(())
+pub struct Day14;
+
+impl Solution for Day14 {
+    const DAY: u8 = 14;
+    const EXAMPLE: Option<&'static str> = Some(EXAMPLE);
+    type Input = Vec<Vec<(usize, usize)>>;
+    type Part1 = usize;
+    type Part2 = usize;
+
+    fn parse(input: &str) -> Result<Self::Input> {
+        parse_input(input)
+    }
+
+    fn part1(lines: &Self::Input) -> Result<Self::Part1> {
+        Ok(part1(build_map_part1(lines)))
+    }
+
+    fn part2(lines: &Self::Input) -> Result<Self::Part2> {
+        Ok(part2(build_map_part2(lines)))
+    }
 }
 
-fn parse_input(input: &str) -> Vec<Vec<(usize, usize)>> {
-    input.split("\n")
-      .map(|line| parse_line(line))
-      .collect()
+fn parse_input(input: &str) -> Result<Vec<Vec<(usize, usize)>>> {
+    let paths = lines(input).iter()
+      .map(parse_line)
+      .collect::<Result<Vec<Vec<(usize, usize)>>, _>>()?;
+    if paths.is_empty() {
+        bail!("empty scan");
+    }
+    Ok(paths)
 }
 
-fn parse_line(line: &str) -> Vec<(usize, usize)> {
-    line.split(" -> ")
-      .filter_map(|pair| pair.split_once(",").map(|(left, right)| (left.parse::<usize>().unwrap(), right.parse::<usize>().unwrap())))
-      .collect()
+fn parse_line(line: &Line) -> Result<Vec<(usize, usize)>, ParseError> {
+    let mut path: Vec<(usize, usize)> = Vec::new();
+    for pair in line.text.split(" -> ") {
+        let (left, right) = pair.split_once(",").ok_or_else(|| line.error(pair, "a point x,y"))?;
+        let point = (line.parse::<usize>(left, "an x coordinate")?, line.parse::<usize>(right, "a y coordinate")?);
+        if path.last().is_some_and(|last| last.0 != point.0 && last.1 != point.1) {
+            return Err(line.error(pair, "a point in line with the previous one"));
+        }
+        path.push(point);
+    }
+    Ok(path)
 }
 
-fn build_map_part1(lines: &Vec<Vec<(usize, usize)>>) -> Map {
-    let bottom: usize = *lines.iter().flatten().map(|(_, y)| y).max().unwrap();
-    let left: usize = *lines.iter().flatten().map(|(x, _)| x).min().unwrap();
-    let right: usize = *lines.iter().flatten().map(|(x, _)| x).max().unwrap();
+fn build_map_part1(lines: &[Vec<(usize, usize)>]) -> Map {
+    let bottom: usize = *lines.iter().flatten().map(|(_, y)| y).max().expect("the scan is not empty");
+    let left: usize = std::cmp::min(*lines.iter().flatten().map(|(x, _)| x).min().unwrap(), 500);
+    let right: usize = std::cmp::max(*lines.iter().flatten().map(|(x, _)| x).max().unwrap(), 500);
 
+    // one column of air either side, so column x of the scan lands at x + 1 - left
     let width = right - left + 3;
     let height = bottom + 2;
-    let offset = left - 1;
+    let offset = left as isize - 1;
     let entry_x = 0;
-    let entry_y = 500 - offset;
-    let mut map = vec![vec![Material::Air; width]; height];
-    map[entry_x][entry_y] = Material::Entry;
+    let entry_y = (500 - offset) as usize;
+    let mut map = Grid::new(height, width, Material::Air);
+    map[(entry_x, entry_y)] = Material::Entry;
     for line in lines {
         for points in line.windows(2) {
             if points[0].0 == points[1].0 {
@@ -53,48 +82,34 @@ fn build_map_part1(lines: &Vec<Vec<(usize, usize)>>) -> Map {
                 let x_start = std::cmp::min(points[0].1, points[1].1);
                 let x_end = std::cmp::max(points[0].1, points[1].1);
                 for x in x_start..(x_end + 1) {
-                    map[x][y - offset] = Material::Rock;
+                    map[(x, (y as isize - offset) as usize)] = Material::Rock;
                 }
             } else {
                 let x = points[0].1;
                 let y_start = std::cmp::min(points[0].0, points[1].0);
                 let y_end = std::cmp::max(points[0].0, points[1].0);
                 for y in y_start..(y_end + 1) {
-                    map[x][y - offset] = Material::Rock;
+                    map[(x, (y as isize - offset) as usize)] = Material::Rock;
                 }
             }
         }
     }
-    Map { map: map, entry: (entry_x, entry_y) }
-}
-
-fn print_map(map: &Vec<Vec<Material>>) -> () {
-    for x in 0..map.len() {
-        for y in 0..map[0].len() {
-            match map[x][y] {
-                Material::Air => print!("."),
-                Material::Rock => print!("#"),
-                Material::Sand => print!("o"),
-                Material::Entry => print!("+"),
-            };
-        }
-        println!("");
-    }
+    Map { map, entry: (entry_x, entry_y) }
 }
 
 fn next_sand(map: &Map) -> (usize, usize) {
-    let (mut x, mut y) = map.entry.clone();
+    let (mut x, mut y) = map.entry;
     loop {
-        if x + 1 == map.map.len() {
+        if x + 1 == map.map.rows() {
             break;
-        } else if map.map[x + 1][y] == Material::Air {
-            x = x + 1;
-        } else if map.map[x + 1][y - 1] == Material::Air {
-            x = x + 1;
-            y = y - 1;
-        } else if map.map[x + 1][y + 1] == Material::Air {
-            x = x + 1;
-            y = y + 1;
+        } else if map.map[(x + 1, y)] == Material::Air {
+            x += 1;
+        } else if map.map[(x + 1, y - 1)] == Material::Air {
+            x += 1;
+            y -= 1;
+        } else if map.map[(x + 1, y + 1)] == Material::Air {
+            x += 1;
+            y += 1;
         } else {
             break;
         }
@@ -102,21 +117,22 @@ fn next_sand(map: &Map) -> (usize, usize) {
     (x, y)
 }
 
-fn build_map_part2(lines: &Vec<Vec<(usize, usize)>>) -> Map {
-    let bottom: usize = *lines.iter().flatten().map(|(_, y)| y).max().unwrap();
-    let left: usize = std::cmp::min(*lines.iter().flatten().map(|(x, _)| x).min().unwrap(), 500 - bottom - 2);
+fn build_map_part2(lines: &[Vec<(usize, usize)>]) -> Map {
+    let bottom: usize = *lines.iter().flatten().map(|(_, y)| y).max().expect("the scan is not empty");
+    // the sand settles at most bottom + 2 columns either side of the entry, which may lie left of x = 0
+    let left: isize = std::cmp::min(*lines.iter().flatten().map(|(x, _)| x).min().unwrap() as isize, 500 - bottom as isize - 2);
     let right: usize = std::cmp::max(*lines.iter().flatten().map(|(x, _)| x).max().unwrap(), 500 + bottom + 2);
 
-    let width = right - left + 3;
+    let width = (right as isize - left + 3) as usize;
     let height = bottom + 3;
     let offset = left - 1;
     let entry_x = 0;
-    let entry_y = 500 - offset;
-    let mut map = vec![vec![Material::Air; width]; height];
+    let entry_y = (500 - offset) as usize;
+    let mut map = Grid::new(height, width, Material::Air);
     for y in 0..width {
-        map[height - 1][y] = Material::Rock;
+        map[(height - 1, y)] = Material::Rock;
     }
-    map[entry_x][entry_y] = Material::Entry;
+    map[(entry_x, entry_y)] = Material::Entry;
     for line in lines {
         for points in line.windows(2) {
             if points[0].0 == points[1].0 {
@@ -124,19 +140,19 @@ fn build_map_part2(lines: &Vec<Vec<(usize, usize)>>) -> Map {
                 let x_start = std::cmp::min(points[0].1, points[1].1);
                 let x_end = std::cmp::max(points[0].1, points[1].1);
                 for x in x_start..(x_end + 1) {
-                    map[x][y - offset] = Material::Rock;
+                    map[(x, (y as isize - offset) as usize)] = Material::Rock;
                 }
             } else {
                 let x = points[0].1;
                 let y_start = std::cmp::min(points[0].0, points[1].0);
                 let y_end = std::cmp::max(points[0].0, points[1].0);
                 for y in y_start..(y_end + 1) {
-                    map[x][y - offset] = Material::Rock;
+                    map[(x, (y as isize - offset) as usize)] = Material::Rock;
                 }
             }
         }
     }
-    Map { map: map, entry: (entry_x, entry_y) }
+    Map { map, entry: (entry_x, entry_y) }
 }
 
 fn part1(input_map: Map) -> usize {
@@ -144,10 +160,10 @@ fn part1(input_map: Map) -> usize {
     let mut count = 0;
     loop {
         let (next_x, next_y) = next_sand(&map);
-        if next_x + 1 == map.map.len() {
+        if next_x + 1 == map.map.rows() {
             break
         }
-        map.map[next_x][next_y] = Material::Sand;
+        map.map[(next_x, next_y)] = Material::Sand;
         count += 1;
     }
     count
@@ -158,7 +174,7 @@ fn part2(input_map: Map) -> usize {
     let mut count = 0;
     loop {
         let (next_x, next_y) = next_sand(&map);
-        map.map[next_x][next_y] = Material::Sand;
+        map.map[(next_x, next_y)] = Material::Sand;
         count += 1;
         if (next_x, next_y) == map.entry {
             break
@@ -167,15 +183,36 @@ fn part2(input_map: Map) -> usize {
     count
 }
 
-// cargo test --bin day14 -- --nocapture
+const EXAMPLE: &str = "498,4 -> 498,6 -> 496,6\n\
+                       503,4 -> 502,4 -> 502,9 -> 494,9";
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    fn render(map: &Grid<Material>) -> String {
+        map.render(|material| match material {
+            Material::Air => '.',
+            Material::Rock => '#',
+            Material::Sand => 'o',
+            Material::Entry => '+',
+        })
+    }
+
     #[test]
     fn test1() {
-        let map = build_map_part1(&parse_input(&INPUT));
-        print_map(&map.map);
+        let map = build_map_part1(&parse_input(EXAMPLE).unwrap());
+        // the scan as drawn in the puzzle, inside a border of air
+        assert_eq!(render(&map.map.crop((0, 1), 10, 10)), "......+...\n\
+                                                           ..........\n\
+                                                           ..........\n\
+                                                           ..........\n\
+                                                           ....#...##\n\
+                                                           ....#...#.\n\
+                                                           ..###...#.\n\
+                                                           ........#.\n\
+                                                           ........#.\n\
+                                                           #########.\n");
         let result = part1(map);
         assert_eq!(result, 24);
     }
@@ -183,12 +220,20 @@ mod tests {
 
     #[test]
     fn test2() {
-        let map = build_map_part2(&parse_input(&INPUT));
-        print_map(&map.map);
+        let map = build_map_part2(&parse_input(EXAMPLE).unwrap());
         let result = part2(map);
         assert_eq!(result, 93);
     }
 
-    const INPUT: &str ="498,4 -> 498,6 -> 496,6\n\
-                        503,4 -> 502,4 -> 502,9 -> 494,9";
+    #[test]
+    fn test_bounds() {
+        assert_eq!(parse_input("\n").unwrap_err().to_string(), "empty scan");
+        let error = parse_input("1,1 -> 2,2").unwrap_err().downcast::<ParseError>().unwrap();
+        assert_eq!((error.line, error.column), (1, 8));
+        // rocks at the left edge, right of the entry and deeper than the entry is far from x = 0
+        assert_eq!(part1(build_map_part1(&parse_input("0,1 -> 0,2").unwrap())), 0);
+        assert_eq!(part1(build_map_part1(&parse_input("600,1 -> 610,1").unwrap())), 0);
+        let deep = build_map_part2(&parse_input("0,600 -> 1,600").unwrap());
+        assert_eq!((deep.entry, deep.map.columns()), ((0, 603), 1207));
+    }
 }
\ No newline at end of file
@@ -1,19 +1,23 @@
-use anyhow::Result;
-use std::fs;
-use std::cmp::Ordering;
+use anyhow::{bail, Result};
+use crate::Solution;
+use crate::geometry::Point;
+use crate::parse::{lines, Line, ParseError};
 use std::cmp::min;
 use std::cmp::max;
 use std::collections::HashSet;
 use regex::Regex;
 
 
+// sensor position and its closest beacon
+type Report = (Point, Point);
+
 #[derive(Debug)]
-struct Intervals {
+pub struct Intervals {
     intervals: Vec<Interval>,
 }
 
 impl Intervals {
-    fn from(intervals: Vec<Interval>) -> Intervals {
+    pub fn from(intervals: Vec<Interval>) -> Intervals {
         let mut sorted_intervals = intervals;
         sorted_intervals.sort();
         let mut merged_intervals: Vec<Interval> = Vec::new();
@@ -33,15 +37,19 @@ impl Intervals {
         Intervals { intervals: merged_intervals }
     }
 
-    fn len(&self) -> usize {
+    pub fn len(&self) -> usize {
         self.intervals.iter().map(|interval| interval.len()).sum()
     }
 
-    fn contain(&self, point: i32) -> bool {
-        self.intervals.iter().any(|interval| interval.contains(point))
+    pub fn is_empty(&self) -> bool {
+        self.intervals.is_empty()
+    }
+
+    pub fn contain(&self, point: i32) -> bool {
+        self.intervals.iter().any(|interval| interval.contain(point))
     }
 
-    fn intersect(&self, interval: Interval) -> Intervals {
+    pub fn intersect(&self, interval: Interval) -> Intervals {
         Intervals::from(self.intervals
             .iter()
             .filter_map(|i| i.intersect(&interval))
@@ -49,36 +57,34 @@ impl Intervals {
     }
 }
 
-#[derive(Debug, Eq, PartialEq, PartialOrd, Clone)]
-struct Interval {
-    start: i32,
-    end: i32,
-}
-
-impl Ord for Interval {
-    fn cmp(&self, other: &Self) -> Ordering {
-        self.start.cmp(&other.start).then(self.end.cmp(&other.end))
-    }
+#[derive(Debug, Eq, PartialEq, Ord, PartialOrd, Clone)]
+pub struct Interval {
+    pub start: i32,
+    pub end: i32,
 }
 
 impl Interval {
-    fn overlap(&self, other: &Self) -> bool {
+    pub fn overlap(&self, other: &Self) -> bool {
         max(self.start, other.start) <= min(self.end, other.end)
     }
 
-    fn merge(&self, other: &Self) -> Interval {
+    pub fn merge(&self, other: &Self) -> Interval {
         Interval { start: min(self.start, other.start), end: max(self.end, other.end) }
     }
 
-    fn len(&self) -> usize {
+    pub fn len(&self) -> usize {
         (self.end - self.start + 1) as usize
     }
 
-    fn contain(&self, point: i32) -> bool {
+    pub fn is_empty(&self) -> bool {
+        self.end < self.start
+    }
+
+    pub fn contain(&self, point: i32) -> bool {
         point >= self.start && point <= self.end
     }
 
-    fn intersect(&self, other: &Self) -> Option<Interval> {
+    pub fn intersect(&self, other: &Self) -> Option<Interval> {
         let start = max(self.start, other.start);
         let end = min(self.end, other.end);
         if start <= end {
@@ -89,99 +95,142 @@ impl Interval {
     }
 }
 
-fn main() -> Result<()> {
-    let lines: Vec<((i32, i32), (i32, i32))> = parse_input(fs::read_to_string("day15.input")?.as_str());
-    println!("{}", part1(&lines, 2000000));
-    println!("{}", part2(&lines, 4000000));
-    Ok(())
+/// Sensor reports together with the row to scan in part 1 and the search area of part 2,
+/// which differ between the example and the real puzzle.
+pub struct Survey {
+    reports: Vec<Report>,
+    row: i32,
+    size: usize,
 }
 
-fn parse_input(input: &str) -> Vec<((i32, i32), (i32, i32))> {
-    input.split("\n")
-      .map(|line| parse_line(line))
-      .collect()
+pub struct Day15;
+
+impl Solution for Day15 {
+    const DAY: u8 = 15;
+    const EXAMPLE: Option<&'static str> = Some(EXAMPLE);
+    type Input = Survey;
+    type Part1 = usize;
+    type Part2 = usize;
+
+    fn parse(input: &str) -> Result<Self::Input> {
+        Ok(Survey { reports: parse_input(input)?, row: 2000000, size: 4000000 })
+    }
+
+    fn example() -> Result<Self::Input> {
+        Ok(Survey { reports: parse_input(EXAMPLE)?, row: 10, size: 20 })
+    }
+
+    fn part1(survey: &Self::Input) -> Result<Self::Part1> {
+        Ok(part1(&survey.reports, survey.row))
+    }
+
+    fn part2(survey: &Self::Input) -> Result<Self::Part2> {
+        part2(&survey.reports, survey.size)
+    }
 }
 
-fn parse_line(line: &str) -> ((i32, i32), (i32, i32)) {
-    let re = Regex::new(r"Sensor at x=(?P<sensor_x>-?\d+), y=(?P<sensor_y>-?\d+): closest beacon is at x=(?P<beacon_x>-?\d+), y=(?P<beacon_y>-?\d+)")
+fn parse_input(input: &str) -> Result<Vec<Report>> {
+    Ok(lines(input).iter()
+      .map(parse_line)
+      .collect::<Result<Vec<Report>, _>>()?)
+}
+
+fn parse_line(line: &Line) -> Result<Report, ParseError> {
+    let re = Regex::new(r"^Sensor at x=(?P<sensor_x>-?\d+), y=(?P<sensor_y>-?\d+): closest beacon is at x=(?P<beacon_x>-?\d+), y=(?P<beacon_y>-?\d+)$")
       .expect("invalid regex");
-    let caps = re.captures(line).expect("parse {line}");
-    ((caps["sensor_x"].parse::<i32>().unwrap(), caps["sensor_y"].parse::<i32>().unwrap()), 
-        (caps["beacon_x"].parse::<i32>().unwrap(), caps["beacon_y"].parse::<i32>().unwrap()))
+    let caps = re.captures(line.text)
+      .ok_or_else(|| line.error(line.text, "Sensor at x=X, y=Y: closest beacon is at x=X, y=Y"))?;
+    let coordinate = |name: &str| line.parse::<i32>(caps.name(name).unwrap().as_str(), "a coordinate");
+    Ok((Point::new(coordinate("sensor_x")?, coordinate("sensor_y")?),
+        Point::new(coordinate("beacon_x")?, coordinate("beacon_y")?)))
 }
 
-fn intersaction(center: (i32, i32), distance: i32, line_y: i32) -> Option<Interval> {
-    let x_offset = distance - (center.1 - line_y).abs();
+fn intersaction(center: Point, distance: i32, line_y: i32) -> Option<Interval> {
+    let x_offset = distance - (center.y - line_y).abs();
     if x_offset > 0 {
-        Some(Interval { start: center.0 - x_offset, end: center.0 + x_offset })
+        Some(Interval { start: center.x - x_offset, end: center.x + x_offset })
     } else {
         None
     }
 }
 
-fn distance(a: (i32, i32), b: (i32, i32)) -> i32 {
-    (a.0 - b.0).abs() + (a.1 - b.1).abs()
-}
-
-fn part1(data: &Vec<((i32, i32), (i32, i32))>, line_y: i32) -> usize {
+fn part1(data: &[Report], line_y: i32) -> usize {
     let intervals: Intervals = Intervals::from(
         data.iter()
-            .map(|(sensor, beacon)| ((sensor.0, sensor.1), distance(*sensor, *beacon)))
+            .map(|(sensor, beacon)| (*sensor, sensor.manhattan(beacon)))
             .filter_map(|(sensor, distance)| intersaction(sensor, distance, line_y))
             .collect());
     let beacon_on_line: HashSet<i32> = data.iter()
-      .filter(|(_, beacon)| beacon.1 == line_y)
-      .map(|(_, beacon)| beacon.0)
+      .filter(|(_, beacon)| beacon.y == line_y)
+      .map(|(_, beacon)| beacon.x)
       .collect();
-    intervals.len() - beacon_on_line.iter().filter(|point| intervals.contains(**point)).count()
+    intervals.len() - beacon_on_line.iter().filter(|point| intervals.contain(**point)).count()
 }
 
-fn part2(data: &Vec<((i32, i32), (i32, i32))>, size: usize) -> usize {
-    let mut length = 0;
+fn part2(data: &[Report], size: usize) -> Result<usize> {
     for line_y in 0..(size+1) {
         let intervals: Intervals = Intervals::from(
             data.iter()
-                .map(|(sensor, beacon)| ((sensor.0, sensor.1), distance(*sensor, *beacon)))
+                .map(|(sensor, beacon)| (*sensor, sensor.manhattan(beacon)))
                 .filter_map(|(sensor, distance)| intersaction(sensor, distance, line_y as i32))
-                .collect());
-        if intervals.intersect(Interval { start: 0, end: size as i32}).len() != size + 1 {
-            length = (intervals.intervals[0].end + 1) as usize * 4000000 + line_y;
-            break;
+                .collect())
+            .intersect(Interval { start: 0, end: size as i32});
+        if intervals.len() != size + 1 {
+            // the intervals are sorted, so the first uncovered x follows the run of them starting at 0
+            let mut x = 0;
+            for interval in &intervals.intervals {
+                if interval.start > x {
+                    break;
+                }
+                x = max(x, interval.end + 1);
+            }
+            return Ok(x as usize * 4000000 + line_y);
         }
     }
-    length
+    bail!("the sensors cover every position from 0 to {size}")
 }
 
-// cargo test --bin day14 -- --nocapture
+const EXAMPLE: &str = "Sensor at x=2, y=18: closest beacon is at x=-2, y=15\n\
+                       Sensor at x=9, y=16: closest beacon is at x=10, y=16\n\
+                       Sensor at x=13, y=2: closest beacon is at x=15, y=3\n\
+                       Sensor at x=12, y=14: closest beacon is at x=10, y=16\n\
+                       Sensor at x=10, y=20: closest beacon is at x=10, y=16\n\
+                       Sensor at x=14, y=17: closest beacon is at x=10, y=16\n\
+                       Sensor at x=8, y=7: closest beacon is at x=2, y=10\n\
+                       Sensor at x=2, y=0: closest beacon is at x=2, y=10\n\
+                       Sensor at x=0, y=11: closest beacon is at x=2, y=10\n\
+                       Sensor at x=20, y=14: closest beacon is at x=25, y=17\n\
+                       Sensor at x=17, y=20: closest beacon is at x=21, y=22\n\
+                       Sensor at x=16, y=7: closest beacon is at x=15, y=3\n\
+                       Sensor at x=14, y=3: closest beacon is at x=15, y=3\n\
+                       Sensor at x=20, y=1: closest beacon is at x=15, y=3";
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
-    #[ignore]
     #[test]
     fn test1() {
-        let result = part1(&parse_input(&INPUT), 10);
+        let result = part1(&parse_input(EXAMPLE).unwrap(), 10);
         assert_eq!(result, 26);
     }
 
     #[test]
     fn test2() {
-        let result = part2(&parse_input(&INPUT), 20);
+        let result = part2(&parse_input(EXAMPLE).unwrap(), 20).unwrap();
         assert_eq!(result, 56000011);
     }
 
-    const INPUT: &str ="Sensor at x=2, y=18: closest beacon is at x=-2, y=15\n\
-                        Sensor at x=9, y=16: closest beacon is at x=10, y=16\n\
-                        Sensor at x=13, y=2: closest beacon is at x=15, y=3\n\
-                        Sensor at x=12, y=14: closest beacon is at x=10, y=16\n\
-                        Sensor at x=10, y=20: closest beacon is at x=10, y=16\n\
-                        Sensor at x=14, y=17: closest beacon is at x=10, y=16\n\
-                        Sensor at x=8, y=7: closest beacon is at x=2, y=10\n\
-                        Sensor at x=2, y=0: closest beacon is at x=2, y=10\n\
-                        Sensor at x=0, y=11: closest beacon is at x=2, y=10\n\
-                        Sensor at x=20, y=14: closest beacon is at x=25, y=17\n\
-                        Sensor at x=17, y=20: closest beacon is at x=21, y=22\n\
-                        Sensor at x=16, y=7: closest beacon is at x=15, y=3\n\
-                        Sensor at x=14, y=3: closest beacon is at x=15, y=3\n\
-                        Sensor at x=20, y=1: closest beacon is at x=15, y=3";
+    #[test]
+    fn test_uncovered() {
+        // no sensor reaches row 0, so its first position is the distress beacon
+        assert_eq!(part2(&parse_input("Sensor at x=0, y=10: closest beacon is at x=0, y=8").unwrap(), 4).unwrap(), 0);
+        assert!(part2(&parse_input("Sensor at x=2, y=2: closest beacon is at x=2, y=8").unwrap(), 4).is_err());
+    }
+
+    #[test]
+    fn test_line_endings() {
+        let crlf = EXAMPLE.replace('\n', "\r\n") + "\r\n";
+        assert_eq!(parse_input(&crlf).unwrap(), parse_input(EXAMPLE).unwrap());
+    }
 }
\ No newline at end of file
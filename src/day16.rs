@@ -0,0 +1,174 @@
+use anyhow::{anyhow, Result};
+use crate::Solution;
+use crate::parse::{lines, Line, ParseError};
+use crate::search::bfs;
+use regex::Regex;
+use std::cmp::max;
+use std::collections::HashMap;
+
+#[derive(Debug, PartialEq)]
+pub struct Valve {
+    name: String,
+    rate: u32,
+    tunnels: Vec<String>,
+}
+
+/// The valves worth opening together with the minutes it takes to walk between any two of them.
+///
+/// Valves `0..rates.len()` are the ones with a positive flow rate; the last row of `distances`
+/// is the starting valve AA.
+#[derive(Debug)]
+pub struct Network {
+    rates: Vec<u32>,
+    distances: Vec<Vec<u32>>,
+}
+
+impl Network {
+    fn new(valves: &[Valve]) -> Result<Network> {
+        let indices: HashMap<&str, usize> = valves.iter().enumerate().map(|(i, valve)| (valve.name.as_str(), i)).collect();
+        let start = *indices.get("AA").ok_or_else(|| anyhow!("there is no valve AA to start from"))?;
+        let mut useful: Vec<usize> = (0..valves.len()).filter(|i| valves[*i].rate > 0).collect();
+        if useful.len() > 20 {
+            return Err(anyhow!("{} valves with a flow rate are too many to track", useful.len()));
+        }
+        useful.push(start);
+        let distances = useful.iter()
+            .map(|from| {
+                let search = bfs([*from], |valve| valves[*valve].tunnels.iter().map(|name| indices[name.as_str()]).collect::<Vec<usize>>(), |_| false);
+                useful.iter().map(|to| search.cost(to).map_or(u32::MAX, |cost| cost as u32)).collect()
+            })
+            .collect();
+        let rates = useful[..useful.len() - 1].iter().map(|i| valves[*i].rate).collect();
+        Ok(Network { rates, distances })
+    }
+
+    /// The most pressure released within `minutes` for every set of opened valves, as a bit mask,
+    /// or for any subset of it.
+    fn best_pressures(&self, minutes: u32) -> Vec<u32> {
+        let mut best = vec![0; 1 << self.rates.len()];
+        self.explore(self.rates.len(), minutes, 0, 0, &mut best);
+        // a set of valves can always do as well as any of its subsets by leaving the rest closed
+        for bit in 0..self.rates.len() {
+            for opened in 0..best.len() {
+                if opened & (1 << bit) != 0 {
+                    best[opened] = max(best[opened], best[opened ^ (1 << bit)]);
+                }
+            }
+        }
+        best
+    }
+
+    fn explore(&self, valve: usize, minutes: u32, opened: usize, pressure: u32, best: &mut Vec<u32>) {
+        best[opened] = max(best[opened], pressure);
+        for next in 0..self.rates.len() {
+            let cost = self.distances[valve][next].saturating_add(1);
+            if opened & (1 << next) == 0 && cost < minutes {
+                let remaining = minutes - cost;
+                self.explore(next, remaining, opened | (1 << next), pressure + remaining * self.rates[next], best);
+            }
+        }
+    }
+}
+
+pub struct Day16;
+
+impl Solution for Day16 {
+    const DAY: u8 = 16;
+    const EXAMPLE: Option<&'static str> = Some(EXAMPLE);
+    type Input = Network;
+    type Part1 = u32;
+    type Part2 = u32;
+
+    fn parse(input: &str) -> Result<Self::Input> {
+        Network::new(&parse_input(input)?)
+    }
+
+    fn part1(network: &Self::Input) -> Result<Self::Part1> {
+        Ok(part1(network))
+    }
+
+    fn part2(network: &Self::Input) -> Result<Self::Part2> {
+        Ok(part2(network))
+    }
+}
+
+fn parse_input(input: &str) -> Result<Vec<Valve>> {
+    let lines = lines(input);
+    let valves = lines.iter().map(parse_line).collect::<Result<Vec<Valve>, _>>()?;
+    for (line, valve) in lines.iter().zip(&valves) {
+        for tunnel in &valve.tunnels {
+            if !valves.iter().any(|other| other.name == *tunnel) {
+                let token = line.text.split([' ', ',']).rev().find(|name| name == tunnel).unwrap();
+                return Err(line.error(token, "a known valve").into());
+            }
+        }
+    }
+    Ok(valves)
+}
+
+fn parse_line(line: &Line) -> Result<Valve, ParseError> {
+    let re = Regex::new(r"^Valve (?P<name>[A-Z]+) has flow rate=(?P<rate>\d+); tunnels? leads? to valves? (?P<tunnels>[A-Z]+(, [A-Z]+)*)$")
+        .expect("invalid regex");
+    let caps = re.captures(line.text)
+        .ok_or_else(|| line.error(line.text, "Valve XX has flow rate=N; tunnels lead to valves YY, ZZ"))?;
+    Ok(Valve {
+        name: caps["name"].to_string(),
+        rate: line.parse::<u32>(caps.name("rate").unwrap().as_str(), "a flow rate")?,
+        tunnels: caps["tunnels"].split(", ").map(|name| name.to_string()).collect(),
+    })
+}
+
+fn part1(network: &Network) -> u32 {
+    network.best_pressures(30)[(1 << network.rates.len()) - 1]
+}
+
+fn part2(network: &Network) -> u32 {
+    // you and the elephant open disjoint sets of valves
+    let best = network.best_pressures(26);
+    let all = best.len() - 1;
+    (0..best.len()).map(|mine| best[mine] + best[all ^ mine]).max().unwrap()
+}
+
+const EXAMPLE: &str = "Valve AA has flow rate=0; tunnels lead to valves DD, II, BB\n\
+                       Valve BB has flow rate=13; tunnels lead to valves CC, AA\n\
+                       Valve CC has flow rate=2; tunnels lead to valves DD, BB\n\
+                       Valve DD has flow rate=20; tunnels lead to valves CC, AA, EE\n\
+                       Valve EE has flow rate=3; tunnels lead to valves FF, DD\n\
+                       Valve FF has flow rate=0; tunnels lead to valves EE, GG\n\
+                       Valve GG has flow rate=0; tunnels lead to valves FF, HH\n\
+                       Valve HH has flow rate=22; tunnel leads to valve GG\n\
+                       Valve II has flow rate=0; tunnels lead to valves AA, JJ\n\
+                       Valve JJ has flow rate=21; tunnel leads to valve II";
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test1() {
+        let result = part1(&Day16::parse(EXAMPLE).unwrap());
+        assert_eq!(result, 1651);
+    }
+
+    #[test]
+    fn test2() {
+        let result = part2(&Day16::parse(EXAMPLE).unwrap());
+        assert_eq!(result, 1707);
+    }
+
+    #[test]
+    fn test_network() {
+        let network = Day16::parse(EXAMPLE).unwrap();
+        assert_eq!(network.rates, vec![13, 2, 20, 3, 22, 21]);
+        // from AA to BB, CC, DD, EE, HH, JJ and AA itself
+        assert_eq!(network.distances[6], vec![1, 2, 1, 2, 5, 2, 0]);
+    }
+
+    #[test]
+    fn test_parse_error() {
+        let error = parse_input("Valve AA has flow rate=x; tunnel leads to valve AA").unwrap_err().downcast::<ParseError>().unwrap();
+        assert_eq!((error.line, error.column), (1, 1));
+        let error = parse_input("Valve AA has flow rate=0; tunnels lead to valves AA, BB").unwrap_err().downcast::<ParseError>().unwrap();
+        assert_eq!((error.line, error.column, error.token.as_str()), (1, 54, "BB"));
+    }
+}
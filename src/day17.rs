@@ -1,123 +1,331 @@
-use anyhow::Result;
-use std::fs;
+use anyhow::{bail, Context, Result};
+use crate::Solution;
+use crate::parse::{blocks, lines, ParseError};
 use std::cmp::max;
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
 
+/// The five rocks of the puzzle in the order they fall, drawn as in the puzzle text.
+pub const SHAPES: &str = "####\n\
+                          \n\
+                          .#.\n\
+                          ###\n\
+                          .#.\n\
+                          \n\
+                          ..#\n\
+                          ..#\n\
+                          ###\n\
+                          \n\
+                          #\n\
+                          #\n\
+                          #\n\
+                          #\n\
+                          \n\
+                          ##\n\
+                          ##";
 
-struct Shape {
-    rocks: Vec<(i16, i16)>,
+#[derive(Debug, Clone, PartialEq)]
+pub struct Shape {
+    /// `(row, column)` of every rock cell, counted from the bottom left corner of the shape.
+    rocks: Vec<(i32, i32)>,
 }
 
 impl Shape {
-    fn from(rocks: Vec<(i16, i16)>) -> Shape {
-        Shape { rocks }
+    fn height(&self) -> i32 {
+        self.rocks.iter().map(|rock| rock.0 + 1).max().unwrap_or(0)
+    }
+
+    fn width(&self) -> i32 {
+        self.rocks.iter().map(|rock| rock.1 + 1).max().unwrap_or(0)
+    }
+
+    /// The shape as one bit mask per row, bottom row first, for every number of columns it can
+    /// be away from the left wall of a chamber `width` wide.
+    fn masks(&self, width: usize) -> Vec<Vec<u8>> {
+        (0..=(width as i32 - self.width()))
+            .map(|column| {
+                let mut masks = vec![0; self.height() as usize];
+                for rock in &self.rocks {
+                    masks[rock.0 as usize] |= 1 << (column + rock.1);
+                }
+                masks
+            })
+            .collect()
+    }
+}
+
+/// Parses shapes drawn with `#` and `.`, top row first, separated by blank lines.
+pub fn parse_shapes(input: &str) -> Result<Vec<Shape>, ParseError> {
+    blocks(input).iter()
+        .map(|block| {
+            let mut rocks = Vec::new();
+            for (row, line) in block.iter().rev().enumerate() {
+                for (column, c) in line.chars() {
+                    match c {
+                        '#' => rocks.push((row as i32, column as i32 - 1)),
+                        '.' => (),
+                        _ => return Err(line.error_at(column, &c.to_string(), "'#' or '.'")),
+                    }
+                }
+            }
+            match rocks.is_empty() {
+                true => Err(block[0].error(block[0].text, "a shape with at least one '#'")),
+                false => Ok(Shape { rocks }),
+            }
+        })
+        .collect()
+}
+
+/// Reads a file of shapes in the format of [`parse_shapes`].
+pub fn load_shapes(path: &Path) -> Result<Vec<Shape>> {
+    let text = fs::read_to_string(path).with_context(|| format!("reading {}", path.display()))?;
+    parse_shapes(&text).with_context(|| format!("reading {}", path.display()))
+}
+
+/// The falling rock simulation: which rocks fall in turn, how wide the chamber is, where each
+/// rock appears and how many of them fall. With `prune` set, the rows below a fully blocked row
+/// are forgotten, since no rock can get past it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Chamber {
+    pub shapes: Vec<Shape>,
+    pub width: usize,
+    /// Columns between the left wall and a new rock, and empty rows between it and the tower.
+    pub spawn: (usize, usize),
+    pub rocks: usize,
+    pub prune: bool,
+}
+
+impl Default for Chamber {
+    /// The chamber of the puzzle, dropping 2022 rocks.
+    fn default() -> Chamber {
+        Chamber { shapes: parse_shapes(SHAPES).unwrap(), width: 7, spawn: (2, 3), rocks: 2022, prune: true }
     }
 }
 
-fn main() -> Result<()> {
-    let pattern: Vec<char> = parse_input(fs::read_to_string("day17.input")?.as_str());
-    println!("{}", part1(&pattern));
-    println!("{}", part2(&pattern));
-    Ok(())
+impl Chamber {
+    /// The height of the tower once all rocks have come to rest, pushed around by `pattern`.
+    ///
+    /// When the next shape, the next jet and the surface of the tower repeat an earlier state, every
+    /// rock after it falls the same way it did back then, so whole repetitions are skipped at once.
+    pub fn tower_height(&self, pattern: &[char]) -> Result<usize> {
+        if pattern.is_empty() {
+            bail!("empty jet pattern");
+        }
+        if self.shapes.is_empty() {
+            bail!("no rock shapes to drop");
+        }
+        if self.width > 8 {
+            bail!("a chamber {} wide does not fit in a row of 8 bits", self.width);
+        }
+        for (index, shape) in self.shapes.iter().enumerate() {
+            if self.spawn.0 as i32 + shape.width() > self.width as i32 {
+                bail!("shape {} does not fit in a chamber {} wide when it appears {} from the wall", index + 1, self.width, self.spawn.0);
+            }
+        }
+        let shapes: Vec<Vec<Vec<u8>>> = self.shapes.iter().map(|shape| shape.masks(self.width)).collect();
+        let mut tower = Tower { rows: Vec::new(), base: 0, tall: 0, pattern_index: 0 };
+        let full = u8::MAX >> (8 - self.width);
+        let mut seen: HashMap<(usize, usize, Vec<usize>), (usize, usize)> = HashMap::new();
+        let mut skipped_height: Option<usize> = None;
+        let mut shape_index = 0;
+        while shape_index < self.rocks {
+            let row = tower.drop_rock(&shapes[shape_index % shapes.len()], self.spawn, pattern);
+            if self.prune {
+                tower.prune(row, full);
+            }
+            shape_index += 1;
+            if skipped_height.is_none() {
+                let state = (shape_index % shapes.len(), tower.pattern_index, tower.surface(self.width));
+                if let Some((previous_index, previous_tall)) = seen.insert(state, (shape_index, tower.tall)) {
+                    let cycle = shape_index - previous_index;
+                    let repeats = (self.rocks - shape_index) / cycle;
+                    shape_index += repeats * cycle;
+                    skipped_height = Some(repeats * (tower.tall - previous_tall));
+                }
+            }
+        }
+        Ok(tower.tall + skipped_height.unwrap_or(0))
+    }
 }
 
-fn parse_input(input: &str) -> Vec<char> {
-    input.chars().collect()
+/// The rocks at rest so far and the next jet to push the falling rock.
+struct Tower {
+    /// One bit mask per row from the bottom up, bit `c` standing for column `c`, starting at
+    /// row `base` once lower rows have been pruned.
+    rows: Vec<u8>,
+    base: usize,
+    tall: usize,
+    pattern_index: usize,
 }
 
-fn part1(pattern: &Vec<char>) -> usize {
-    let shapes: Vec<Shape> = vec![
-        Shape::from(vec![(0, 0), (0, 1), (0, 2), (0, 3)]),
-        Shape::from(vec![(0, 1), (1, 0), (1, 1), (1, 2), (2, 1)]),
-        Shape::from(vec![(0, 0), (0, 1), (0, 2), (1, 2), (2, 2)]),
-        Shape::from(vec![(0, 0), (1, 0), (2, 0), (3, 0)]),
-        Shape::from(vec![(0, 0), (0, 1), (1, 0), (1, 1)])];
-    let mut chamber: Vec<Vec<bool>> = vec![vec![false; 7]; 6];
-    let mut tall: i16 = 0;
-    let mut pattern_index: usize = 0;
-    for shape_index in 0..100 {
-        let shape = &shapes[shape_index % 5];
-        let mut position = (tall + 3 as i16, 2 as i16);
+impl Tower {
+    /// Lets a rock fall, given as its masks for each column, and returns the row it rests on.
+    fn drop_rock(&mut self, shape: &[Vec<u8>], spawn: (usize, usize), pattern: &[char]) -> usize {
+        let (mut row, mut column) = (self.tall + spawn.1, spawn.0);
         loop {
-            match pattern[pattern_index % pattern.len()] {
-                '<' => if !collide(&chamber, (position.0 as i16, position.1 as i16 - 1), &shape) { position.1 -= 1 },
-                '>' => if !collide(&chamber, (position.0 as i16, position.1 as i16 + 1), &shape) { position.1 += 1 },
+            let pushed = match pattern[self.pattern_index] {
+                '<' => column.checked_sub(1),
+                '>' => Some(column + 1).filter(|next| *next < shape.len()),
                 _ => panic!(),
+            };
+            if let Some(next) = pushed.filter(|next| !self.collide(&shape[*next], row)) {
+                column = next;
             }
-            pattern_index += 1;
-            if !collide(&chamber, (position.0 as i16 - 1, position.1 as i16), &shape) {
-                position.0 -= 1;
+            self.pattern_index = (self.pattern_index + 1) % pattern.len();
+            if row > 0 && !self.collide(&shape[column], row - 1) {
+                row -= 1;
             } else {
                 break;
             }
         }
-        for rock in &shape.rocks {
-            chamber[(position.0 + rock.0) as usize][(position.1 + rock.1) as usize] = true;
-            tall = max(tall, position.0 + rock.0 + 1);
+        let masks = &shape[column];
+        let top = row + masks.len();
+        if self.rows.len() < top - self.base {
+            self.rows.resize(top - self.base, 0);
         }
-        while (tall + 6) as usize >= chamber.len() {
-            chamber.push(vec![false; 7]);
+        for (i, mask) in masks.iter().enumerate() {
+            self.rows[row - self.base + i] |= mask;
         }
+        self.tall = max(self.tall, top);
+        row
     }
-    print_chamber(&chamber);
-    tall as usize
-}
 
-fn print_chamber(chamber: &Vec<Vec<bool>>) -> () {
-    for row in chamber.iter().rev() {
-        println!("|{}|", row.iter().map(|has_rock| if *has_rock { '#' } else { '.' }).collect::<String>());
+    fn collide(&self, masks: &[u8], row: usize) -> bool {
+        row < self.base || masks.iter().enumerate().any(|(i, mask)| self.rows.get(row - self.base + i).is_some_and(|rocks| rocks & mask != 0))
+    }
+
+    /// Forgets the rows below the highest fully blocked row among those from `row` upwards.
+    fn prune(&mut self, row: usize, full: u8) {
+        if let Some(blocked) = self.rows[row - self.base..].iter().rposition(|rocks| *rocks == full) {
+            let blocked = row - self.base + blocked;
+            self.rows.drain(..blocked);
+            self.base += blocked;
+        }
+    }
+
+    /// How far below the top of the tower the highest rock of each column lies.
+    fn surface(&self, width: usize) -> Vec<usize> {
+        (0..width)
+            .map(|column| self.rows[..self.tall - self.base].iter().rev().take_while(|rocks| *rocks & (1 << column) == 0).count())
+            .collect()
     }
-    println!("+-------+");
 }
 
-fn collide(chamber: &Vec<Vec<bool>>, position: (i16, i16), shape: &Shape) -> bool {
-    if position.0 < 0 {
-        return true;
+pub struct Day17;
+
+impl Solution for Day17 {
+    const DAY: u8 = 17;
+    const EXAMPLE: Option<&'static str> = Some(EXAMPLE);
+    type Input = Vec<char>;
+    type Part1 = usize;
+    type Part2 = usize;
+
+    fn parse(input: &str) -> Result<Self::Input> {
+        parse_input(input)
     }
-    if position.1 < 0 || position.1 > 6 {
-        return true;
+
+    fn part1(pattern: &Self::Input) -> Result<Self::Part1> {
+        part1(pattern)
     }
-    for rock in &shape.rocks {
-        if position.1 + rock.1 < 0 || position.1 + rock.1 > 6 {
-            return true;
-        }
-        let x = (position.0 + rock.0) as usize;
-        let y = (position.1 + rock.1) as usize;
-        if chamber[x][y] {
-            return true;
-        }
+
+    fn part2(pattern: &Self::Input) -> Result<Self::Part2> {
+        part2(pattern)
     }
-    false
 }
 
-fn part2(pattern: &Vec<char>) -> usize {
-    // 55 + 573065902 * 1745 + 955 = 1000000000000
-    // 11 + 573065902 * 349 + 191 = 200000000000
+fn parse_input(input: &str) -> Result<Vec<char>> {
+    let mut pattern: Vec<char> = Vec::new();
+    for line in lines(input) {
+        for (column, c) in line.chars() {
+            match c {
+                '<' | '>' => pattern.push(c),
+                _ => bail!(line.error_at(column, &c.to_string(), "'<' or '>'")),
+            }
+        }
+    }
+    if pattern.is_empty() {
+        bail!("empty jet pattern");
+    }
+    Ok(pattern)
+}
 
-    // number -> height
-    // 55 -> 80
-    // 1010 -> 1586
-    // 1800 -> 2858
+fn part1(pattern: &[char]) -> Result<usize> {
+    Chamber::default().tower_height(pattern)
+}
 
-    80 + 573065902 * 2778 + 1586 - 80
+fn part2(pattern: &[char]) -> Result<usize> {
+    Chamber { rocks: 1_000_000_000_000, ..Chamber::default() }.tower_height(pattern)
 }
 
-// cargo test --bin day17 -- --nocapture
+const EXAMPLE: &str = ">>><<><>><<<>><>>><<<>>><<<><<<>><>><<>>";
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
     #[test]
     fn test1() {
-        let result = part1(&parse_input(&INPUT));
+        let result = part1(&parse_input(EXAMPLE).unwrap()).unwrap();
         assert_eq!(result, 3068);
     }
 
-    #[ignore]
     #[test]
     fn test2() {
-        let result = 28571428571 * 53 + 25;
-        assert_eq!(result, 1514285714288usize);
+        let result = part2(&parse_input(EXAMPLE).unwrap()).unwrap();
+        assert_eq!(result, 1514285714288);
     }
 
-    const INPUT: &str =">>><<><>><<<>><>>><<<>>><<<><<<>><>><<>>";
-}
\ No newline at end of file
+    #[test]
+    fn test_shapes() {
+        let shapes = parse_shapes(SHAPES).unwrap();
+        assert_eq!(shapes.len(), 5);
+        assert_eq!(shapes[2].rocks, vec![(0, 0), (0, 1), (0, 2), (1, 2), (2, 2)]);
+        let error = parse_shapes("##\n\n#x").unwrap_err();
+        assert_eq!((error.line, error.column), (3, 2));
+    }
+
+    #[test]
+    fn test_load_shapes() {
+        let dir = std::env::temp_dir().join(format!("aoc2022-day17-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("shapes.txt");
+        fs::write(&path, format!("{SHAPES}\n")).unwrap();
+        let shapes = load_shapes(&path).unwrap();
+        let chamber = Chamber { shapes, ..Chamber::default() };
+        assert_eq!(chamber.tower_height(&parse_input(EXAMPLE).unwrap()).unwrap(), 3068);
+
+        fs::write(&path, "##\n\n#x").unwrap();
+        let error = load_shapes(&path).unwrap_err();
+        assert_eq!(error.downcast_ref::<ParseError>().map(|error| (error.line, error.column)), Some((3, 2)));
+        fs::remove_dir_all(&dir).unwrap();
+        let error = load_shapes(&path).unwrap_err();
+        assert_eq!(error.to_string(), format!("reading {}", path.display()));
+        assert!(error.downcast_ref::<std::io::Error>().is_some());
+    }
+
+    #[test]
+    fn test_chamber() {
+        let pattern = parse_input(EXAMPLE).unwrap();
+        // the first rocks of the example, as drawn in the puzzle
+        let chamber = Chamber { rocks: 3, ..Chamber::default() };
+        assert_eq!(chamber.tower_height(&pattern).unwrap(), 6);
+        // a flat rock as wide as the chamber stacks straight up
+        let wall = Chamber { shapes: parse_shapes("###").unwrap(), width: 3, spawn: (0, 3), rocks: 10, prune: false };
+        assert_eq!(wall.tower_height(&pattern).unwrap(), 10);
+        let narrow = Chamber { width: 5, ..Chamber::default() };
+        assert!(narrow.tower_height(&pattern).is_err());
+        let wide = Chamber { width: 9, ..Chamber::default() };
+        assert!(wide.tower_height(&pattern).is_err());
+    }
+
+    #[test]
+    fn test_prune() {
+        let pattern = parse_input(EXAMPLE).unwrap();
+        for rocks in [1, 10, 100, 2022] {
+            let pruned = Chamber { rocks, ..Chamber::default() };
+            let kept = Chamber { prune: false, ..pruned.clone() };
+            assert_eq!(pruned.tower_height(&pattern).unwrap(), kept.tower_height(&pattern).unwrap());
+        }
+    }
+}
@@ -1,191 +1,128 @@
-use anyhow::Result;
-use std::fs;
-use num::abs;
-use std::collections::VecDeque;
-
-#[derive(Debug)]
-struct Cube {
-    x: i32,
-    y: i32,
-    z: i32,
-}
-
-impl Cube {
-    fn adjacent(&self, other: &Self) -> bool {
-        self.distance(other) == 1
-    }
-
-    fn distance(&self, other: &Self) -> usize {
-        (abs(self.x - other.x) + abs(self.y - other.y) + abs(self.z - other.z)) as usize
+use anyhow::{bail, Result};
+use crate::Solution;
+use crate::geometry::{Bounds, Point3};
+use crate::parse::{lines, Line, ParseError};
+use crate::search::bfs;
+use std::collections::HashSet;
+
+pub struct Day18;
+
+impl Solution for Day18 {
+    const DAY: u8 = 18;
+    const EXAMPLE: Option<&'static str> = Some(EXAMPLE);
+    type Input = Vec<Point3>;
+    type Part1 = usize;
+    type Part2 = usize;
+
+    fn parse(input: &str) -> Result<Self::Input> {
+        parse_input(input)
     }
 
-    fn adjacent_cubes(&self) -> Vec<Cube> {
-        vec![
-            Cube { x: self.x + 1, y: self.y, z: self.z },
-            Cube { x: self.x - 1, y: self.y, z: self.z },
-            Cube { x: self.x, y: self.y + 1, z: self.z },
-            Cube { x: self.x, y: self.y - 1, z: self.z },
-            Cube { x: self.x, y: self.y, z: self.z + 1 },
-            Cube { x: self.x, y: self.y, z: self.z - 1 },
-        ]
+    fn part1(cubes: &Self::Input) -> Result<Self::Part1> {
+        Ok(part1(cubes))
     }
 
-    fn translate(&self, offset: (i32, i32, i32)) -> Cube {
-        Cube { x: self.x - offset.0, y: self.y - offset.1, z: self.z - offset.2 }
+    fn part2(cubes: &Self::Input) -> Result<Self::Part2> {
+        Ok(part2(cubes))
     }
 }
 
-fn main() -> Result<()> {
-    let cubes: Vec<Cube> = parse_input(fs::read_to_string("day18.input")?.as_str());
-    println!("{}", part1(&cubes));
-    println!("{}", part2(&cubes));
-    Ok(())
-}
-
-fn parse_input(input: &str) -> Vec<Cube> {
-    input.split("\n").map(|line| parse_line(line)).collect()
+fn parse_input(input: &str) -> Result<Vec<Point3>> {
+    let cubes = lines(input).iter().map(parse_line).collect::<Result<Vec<Point3>, _>>()?;
+    if cubes.is_empty() {
+        bail!("empty scan");
+    }
+    Ok(cubes)
 }
 
-fn parse_line(line: &str) -> Cube {
-    let values: Vec<i32> = line.split(",").flat_map(|s| s.parse::<i32>()).collect();
-    Cube { x: values[0], y: values[1], z: values[2] }
+fn parse_line(line: &Line) -> Result<Point3, ParseError> {
+    let values: Vec<i32> = line.text.split(",").map(|s| line.parse::<i32>(s, "a coordinate")).collect::<Result<Vec<i32>, _>>()?;
+    match values[..] {
+        [x, y, z] => Ok(Point3::new(x, y, z)),
+        _ => Err(line.error(line.text, "three coordinates x,y,z")),
+    }
 }
 
-fn part1(cubes: &Vec<Cube>) -> usize {
+fn part1(cubes: &[Point3]) -> usize {
     let n = cubes.len();
     let mut surface_area = 0;
     for i in 0..n {
         surface_area += 6;
         for j in 0..n {
-            if i != j {
-                if cubes[i].adjacent(&cubes[j]) {
+            if i != j
+                && cubes[i].manhattan(&cubes[j]) == 1 {
                     surface_area -= 1;
                 }
-            }
         }
     }
     surface_area
 }
 
-#[derive(Debug, Clone, PartialEq)]
-enum State {
-    Lava,
-    External,
-    Internal,
+fn part2(cubes: &[Point3]) -> usize {
+    let lava: HashSet<Point3> = cubes.iter().copied().collect();
+    // flood the air from a corner of a box one cube larger than the droplet on every side
+    let bounds = Bounds::of(cubes.iter().copied()).expect("the scan is not empty");
+    let air = Bounds { min: bounds.min - Point3::new(1, 1, 1), max: bounds.max + Point3::new(1, 1, 1) };
+    let outside = bfs([air.min],
+        |cube| cube.neighbours6().into_iter().filter(|next| air.contains(*next) && !lava.contains(next)).collect::<Vec<Point3>>(),
+        |_| false);
+    cubes.iter()
+        .flat_map(|cube| cube.neighbours6())
+        .filter(|next| outside.contains(next))
+        .count()
 }
 
-fn part2(cubes: &Vec<Cube>) -> usize {
-    let max_x = cubes.iter().map(|cube| cube.x).max().unwrap();
-    let min_x = cubes.iter().map(|cube| cube.x).min().unwrap();
-    let max_y = cubes.iter().map(|cube| cube.y).max().unwrap();
-    let min_y = cubes.iter().map(|cube| cube.y).min().unwrap();
-    let max_z = cubes.iter().map(|cube| cube.z).max().unwrap();
-    let min_z = cubes.iter().map(|cube| cube.z).min().unwrap();
-    let x_size = (max_x - min_x + 1) as usize;
-    let y_size = (max_y - min_y + 1) as usize;
-    let z_size = (max_z - min_z + 1) as usize;
-    let offset = (min_x, min_y, min_z);
-    let mut states: Vec<Vec<Vec<State>>> = vec![vec![vec![State::Internal; z_size]; y_size]; x_size];
-    let mut visited: Vec<Vec<Vec<bool>>> = vec![vec![vec![false; z_size]; y_size]; x_size];
-    for cube in cubes {
-        let translated_cube = cube.translate(offset);
-        let x = translated_cube.x as usize;
-        let y = translated_cube.y as usize;
-        let z = translated_cube.z as usize;
-        states[x][y][z] = State::Lava;
-    }
-    let mut queue = VecDeque::new();
-
-    for x in 0..x_size {
-        for y in 0..y_size {
-            for z in 0..z_size {
-                if (x == 0 || y == 0 || z == 0 || x == x_size - 1 || y == y_size - 1 || z == z_size - 1)
-                    && states[x][y][z] != State::Lava {
-                    states[x][y][z] = State::External;
-                    visited[x][y][z] = true;
-                    queue.push_back(Cube { x: x as i32, y: y as i32, z: z as i32});
-                }
-            }
-        }
-    }
-    
-    while let Some(current) = queue.pop_front() {
-        for cube in current.adjacent_cubes() {
-            if cube.x >= 0 && cube.x < x_size as i32 
-            && cube.y >= 0 && cube.y < y_size as i32 
-            && cube.z >= 0 && cube.z < z_size as i32 {
-                let x = cube.x as usize;
-                let y = cube.y as usize;
-                let z = cube.z as usize;
-                if !visited[x][y][z] && states[x][y][z] != State::Lava {
-                    states[x][y][z] = State::External;
-                    visited[x][y][z] = true;
-                    queue.push_back(cube);
-                }
-            }
-        }
-    }
-
-    let mut all_cubes: Vec<Cube> = vec![];
-    for x in 0..x_size {
-        for y in 0..y_size {
-            for z in 0..z_size {
-                if states[x][y][z] != State::External {
-                    all_cubes.push(Cube { x: x as i32, y: y as i32, z: z as i32 })
-                }
-            }
-        }
-    }
-    part1(&all_cubes)
-}
+const EXAMPLE: &str = "2,2,2\n\
+                       1,2,2\n\
+                       3,2,2\n\
+                       2,1,2\n\
+                       2,3,2\n\
+                       2,2,1\n\
+                       2,2,3\n\
+                       2,2,4\n\
+                       2,2,6\n\
+                       1,2,5\n\
+                       3,2,5\n\
+                       2,1,5\n\
+                       2,3,5";
 
-// cargo test --bin day17 -- --nocapture
 #[cfg(test)]
 mod tests {
     use super::*;
 
     #[test]
     fn test0() {
-        let result = part1(&parse_input("1,1,1\n2,1,1"));
+        let result = part1(&parse_input("1,1,1\n2,1,1").unwrap());
         assert_eq!(result, 10);
     }
 
     #[test]
     fn test01() {
-        let result = part1(&parse_input("1,1,1\n2,1,1\n1,2,1"));
+        let result = part1(&parse_input("1,1,1\n2,1,1\n1,2,1").unwrap());
         assert_eq!(result, 14);
     }
 
     #[test]
     fn test02() {
-        let result = part1(&parse_input("2,2,2\n1,2,2\n3,2,2\n2,1,2\n2,3,2"));
+        let result = part1(&parse_input("2,2,2\n1,2,2\n3,2,2\n2,1,2\n2,3,2").unwrap());
         assert_eq!(result, 22);
     }
 
 
     #[test]
     fn test1() {
-        let result = part1(&parse_input(&INPUT));
+        let result = part1(&parse_input(EXAMPLE).unwrap());
         assert_eq!(result, 64);
     }
 
     #[test]
     fn test2() {
-        let result = part2(&parse_input(&INPUT));
+        let result = part2(&parse_input(EXAMPLE).unwrap());
         assert_eq!(result, 58);
     }
 
-    const INPUT: &str ="2,2,2\n\
-                        1,2,2\n\
-                        3,2,2\n\
-                        2,1,2\n\
-                        2,3,2\n\
-                        2,2,1\n\
-                        2,2,3\n\
-                        2,2,4\n\
-                        2,2,6\n\
-                        1,2,5\n\
-                        3,2,5\n\
-                        2,1,5\n\
-                        2,3,5";
+    #[test]
+    fn test_empty() {
+        assert_eq!(parse_input("\n").unwrap_err().to_string(), "empty scan");
+    }
 }
\ No newline at end of file
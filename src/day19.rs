@@ -1,130 +1,481 @@
-use anyhow::Result;
-use std::fs;
+use anyhow::{anyhow, bail, Result};
+use crate::Solution;
+use crate::parse::{lines, Line, ParseError};
 use regex::Regex;
-use std::cmp::max;
+use std::cmp::{max, min};
+use std::collections::HashSet;
+use std::fmt;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
 
-#[derive(Debug)]
-struct Blueprint {
+/// The most kinds of resource a blueprint can deal in.
+const MAX_RESOURCES: usize = 8;
+
+/// The resource the puzzle asks for.
+const GEODE: &str = "geode";
+
+/// A factory recipe book: one kind of robot per resource, numbered in the order the blueprint
+/// describes them, and what each robot costs. The factory starts out with one robot collecting
+/// the first resource.
+#[derive(Debug, PartialEq)]
+pub struct Blueprint {
     number: u16,
-    ore: u16,
-    clay: u16,
-    obsidian: (u16, u16),
-    geode: (u16, u16),
+    resources: Vec<String>,
+    /// `costs[robot][resource]`, the amount of `resource` a robot collecting `robot` costs.
+    costs: Vec<Vec<u16>>,
 }
 
 impl Blueprint {
-    fn max_ore(&self) -> u16 {
-        max(max(self.ore, self.clay), max(self.obsidian.0, self.geode.0))
+    /// The index of the resource called `name`, which is also that of the robot collecting it.
+    pub fn resource(&self, name: &str) -> Option<usize> {
+        self.resources.iter().position(|resource| resource == name)
+    }
+
+    pub fn resources(&self) -> &[String] {
+        &self.resources
+    }
+
+    pub fn cost(&self, robot: usize, resource: usize) -> u16 {
+        self.costs[robot][resource]
+    }
+
+    /// Plays out a plan naming the robot to start building in each minute, if any, and fails on
+    /// the first robot that cannot be paid for at the start of its minute.
+    pub fn replay(&self, builds: &[Option<usize>]) -> Result<Schedule> {
+        let count = self.resources.len();
+        let mut resources = vec![0; count];
+        let mut robots = vec![0; count];
+        robots[0] = 1;
+        let mut minutes = Vec::with_capacity(builds.len());
+        for (minute, built) in builds.iter().enumerate() {
+            if let Some(robot) = *built {
+                if robot >= count {
+                    bail!("minute {}: there is no robot {}", minute + 1, robot);
+                }
+                if self.costs[robot].iter().zip(&resources).any(|(cost, stock)| cost > stock) {
+                    bail!("minute {}: not enough resources to build a {} robot", minute + 1, self.resources[robot]);
+                }
+                for (stock, cost) in resources.iter_mut().zip(&self.costs[robot]) {
+                    *stock -= cost;
+                }
+            }
+            for (stock, collecting) in resources.iter_mut().zip(&robots) {
+                *stock += collecting;
+            }
+            if let Some(robot) = *built {
+                robots[robot] += 1;
+            }
+            minutes.push(Minute { built: *built, resources: resources.clone(), robots: robots.clone() });
+        }
+        Ok(Schedule { resources: self.resources.clone(), costs: self.costs.clone(), minutes })
+    }
+}
+
+/// What happened in one minute: the robot started, if any, and the resources in stock and the
+/// robots at work once the minute is over.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Minute {
+    pub built: Option<usize>,
+    pub resources: Vec<u16>,
+    pub robots: Vec<u16>,
+}
+
+/// A blueprint played out minute by minute; printed, it tells the story the way the puzzle does.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Schedule {
+    resources: Vec<String>,
+    costs: Vec<Vec<u16>>,
+    pub minutes: Vec<Minute>,
+}
+
+impl Schedule {
+    /// The amount of `resource` in stock by the end.
+    pub fn collected(&self, resource: usize) -> u16 {
+        self.minutes.last().map_or(0, |minute| minute.resources[resource])
     }
 
-    fn max_clay(&self) -> u16 {
-        self.obsidian.1
+    /// The robot started in each minute, as [`Blueprint::replay`] takes it.
+    pub fn builds(&self) -> Vec<Option<usize>> {
+        self.minutes.iter().map(|minute| minute.built).collect()
     }
 
-    fn max_obsidian(&self) -> u16 {
-        self.geode.1
+    fn robot(&self, robot: usize) -> String {
+        match self.resources[robot].as_str() {
+            GEODE => "geode-cracking robot".to_string(),
+            name => format!("{name}-collecting robot"),
+        }
     }
 }
 
-fn main() -> Result<()> {
-    let blueprints: Vec<Blueprint> = parse_input(fs::read_to_string("day19.input")?.as_str());
-    println!("{}", part1(&blueprints));
-    println!("{}", part2(&blueprints));
-    Ok(())
+impl fmt::Display for Schedule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let article = |name: &str| if name.starts_with(['a', 'e', 'i', 'o', 'u']) { "an" } else { "a" };
+        for (index, minute) in self.minutes.iter().enumerate() {
+            if index > 0 {
+                writeln!(f)?;
+            }
+            writeln!(f, "== Minute {} ==", index + 1)?;
+            if let Some(built) = minute.built {
+                let spent: Vec<String> = (0..self.resources.len())
+                    .filter(|resource| self.costs[built][*resource] > 0)
+                    .map(|resource| format!("{} {}", self.costs[built][resource], self.resources[resource]))
+                    .collect();
+                writeln!(f, "Spend {} to start building {} {}.", spent.join(" and "), article(&self.resources[built]), self.robot(built))?;
+            }
+            for (resource, name) in self.resources.iter().enumerate() {
+                let working = minute.robots[resource] - u16::from(minute.built == Some(resource));
+                if working == 0 {
+                    continue;
+                }
+                let plural = if working == 1 { "" } else { "s" };
+                let verb = if working == 1 { "s" } else { "" };
+                let stock = minute.resources[resource];
+                match name.as_str() {
+                    GEODE => writeln!(
+                        f,
+                        "{working} {}{plural} crack{verb} {working} geode{plural}; you now have {stock} open geode{}.",
+                        self.robot(resource),
+                        if stock == 1 { "" } else { "s" },
+                    )?,
+                    _ => writeln!(f, "{working} {}{plural} collect{verb} {working} {name}; you now have {stock} {name}.", self.robot(resource))?,
+                }
+            }
+            if let Some(built) = minute.built {
+                writeln!(f, "The new {} is ready; you now have {} of them.", self.robot(built), minute.robots[built])?;
+            }
+        }
+        Ok(())
+    }
 }
 
-fn parse_input(input: &str) -> Vec<Blueprint> {
-    input.split("\n").map(|line| parse_line(line)).collect()
+/// The most of the `target` resource `blueprint` can have in stock after `minutes`.
+pub fn max_collected(blueprint: &Blueprint, target: usize, minutes: u16) -> u16 {
+    Optimiser::run(blueprint, target, minutes).best
 }
 
-fn parse_line(line: &str) -> Blueprint {
-    let re = Regex::new(r"Blueprint (?P<number1>\d+): Each ore robot costs (?P<number2>\d+) ore. Each clay robot costs (?P<number3>\d+) ore. Each obsidian robot costs (?P<number4>\d+) ore and (?P<number5>\d+) clay. Each geode robot costs (?P<number6>\d+) ore and (?P<number7>\d+) obsidian.")
-    .expect("invalid regex");
-    let caps = re.captures(line).expect("parse {line}");
-    Blueprint {
-        number: caps["number1"].parse::<u16>().unwrap(),
-        ore: caps["number2"].parse::<u16>().unwrap(),
-        clay: caps["number3"].parse::<u16>().unwrap(),
-        obsidian: (caps["number4"].parse::<u16>().unwrap(), caps["number5"].parse::<u16>().unwrap()),
-        geode: (caps["number6"].parse::<u16>().unwrap(), caps["number7"].parse::<u16>().unwrap()),
+/// A plan collecting the most of the `target` resource `blueprint` can within `minutes`.
+pub fn best_schedule(blueprint: &Blueprint, target: usize, minutes: u16) -> Schedule {
+    let optimiser = Optimiser::run(blueprint, target, minutes);
+    let mut builds = vec![None; minutes as usize];
+    for (remaining, robot) in optimiser.plan {
+        builds[(minutes - remaining - 1) as usize] = Some(robot);
     }
+    blueprint.replay(&builds).expect("the optimiser only builds affordable robots")
 }
 
-fn part1(blueprints: &Vec<Blueprint>) -> u16 {
-    blueprints.iter()
-        .map(|blueprint| blueprint.number * dfs(&blueprint, (0,0,0), (1,0,0), (false, false, false), 23))
-        .sum()
+/// The most of the resource called `target` each blueprint can collect within `minutes`, in the
+/// order given, searched on up to `threads` threads that each take the next blueprint nobody has
+/// started yet.
+pub fn evaluate(blueprints: &[Blueprint], target: &str, minutes: u16, threads: usize) -> Result<Vec<u16>> {
+    let targets = blueprints.iter()
+        .map(|blueprint| blueprint.resource(target).ok_or_else(|| anyhow!("blueprint {} has no {} robot", blueprint.number, target)))
+        .collect::<Result<Vec<usize>>>()?;
+    let next = AtomicUsize::new(0);
+    let mut collected = vec![0; blueprints.len()];
+    thread::scope(|scope| {
+        let workers: Vec<_> = (0..threads.clamp(1, max(blueprints.len(), 1)))
+            .map(|_| scope.spawn(|| {
+                let mut done = Vec::new();
+                loop {
+                    let index = next.fetch_add(1, Ordering::Relaxed);
+                    let Some(blueprint) = blueprints.get(index) else { break };
+                    done.push((index, max_collected(blueprint, targets[index], minutes)));
+                }
+                done
+            }))
+            .collect();
+        for worker in workers {
+            for (index, amount) in worker.join().expect("blueprint search panicked") {
+                collected[index] = amount;
+            }
+        }
+    });
+    Ok(collected)
 }
 
-fn dfs(blueprint: &Blueprint, resource: (u16, u16, u16), robots: (u16, u16, u16), skip: (bool, bool, bool), time: u16) -> u16 {
-    if time == 0 {
-        return 0;
+/// One thread per core, or a single one if that cannot be told.
+fn default_threads() -> usize {
+    thread::available_parallelism().map_or(1, |threads| threads.get())
+}
+
+/// A point in the plan: the minutes left, and the resources in stock and the robots collecting
+/// them, with unused slots beyond the blueprint's resources left at zero.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+struct State {
+    minutes: u16,
+    resources: [u16; MAX_RESOURCES],
+    robots: [u16; MAX_RESOURCES],
+}
+
+/// A depth-first search that picks which robot to build next and waits until it is affordable.
+struct Optimiser {
+    count: usize,
+    target: usize,
+    costs: [[u16; MAX_RESOURCES]; MAX_RESOURCES],
+    max_spend: [u16; MAX_RESOURCES],
+    best: u16,
+    seen: HashSet<State>,
+    /// The robots built on the way to the current state, with the minutes left once each is ready.
+    path: Vec<(u16, usize)>,
+    /// The path to the best state found so far.
+    plan: Vec<(u16, usize)>,
+}
+
+impl Optimiser {
+    fn run(blueprint: &Blueprint, target: usize, minutes: u16) -> Optimiser {
+        let count = blueprint.resources.len();
+        let mut costs = [[0; MAX_RESOURCES]; MAX_RESOURCES];
+        for (robot, cost) in blueprint.costs.iter().enumerate() {
+            costs[robot][..count].copy_from_slice(cost);
+        }
+        // no robot can spend more of a resource in a minute than the dearest recipe asks for,
+        // but there is no such thing as too much of the target
+        let mut max_spend = [0; MAX_RESOURCES];
+        for (resource, spend) in max_spend.iter_mut().enumerate() {
+            *spend = if resource == target { u16::MAX } else { costs.iter().map(|cost| cost[resource]).max().unwrap() };
+        }
+        let mut optimiser = Optimiser { count, target, costs, max_spend, best: 0, seen: HashSet::new(), path: Vec::new(), plan: Vec::new() };
+        let mut robots = [0; MAX_RESOURCES];
+        robots[0] = 1;
+        optimiser.search(State { minutes, resources: [0; MAX_RESOURCES], robots });
+        optimiser
+    }
+
+    fn search(&mut self, state: State) {
+        let minutes = state.minutes;
+        let collected = state.resources[self.target] + state.robots[self.target] * minutes;
+        if collected > self.best {
+            self.best = collected;
+            self.plan = self.path.clone();
+        }
+        // a robot built in the last minute collects nothing, so at best every minute before it
+        // brings a target robot that collects one per remaining minute
+        if minutes <= 1 || collected + minutes * (minutes - 1) / 2 <= self.best {
+            return;
+        }
+        // a state seen before was explored against a lower or equal best, so it cannot improve on it
+        if !self.seen.insert(state) {
+            return;
+        }
+        let target = self.target;
+        let order = std::iter::once(target).chain((0..self.count).rev().filter(move |robot| *robot != target));
+        for robot in order {
+            if state.robots[robot] >= self.max_spend[robot] {
+                continue;
+            }
+            let Some(wait) = self.wait(&state, robot) else { continue };
+            if wait + 1 >= minutes {
+                continue;
+            }
+            let remaining = minutes - wait - 1;
+            let mut next = State { minutes: remaining, ..state };
+            for resource in 0..self.count {
+                let stock = state.resources[resource] + state.robots[resource] * (wait + 1) - self.costs[robot][resource];
+                // more than can ever be spent is as good as exactly that much
+                next.resources[resource] = match resource == self.target {
+                    true => stock,
+                    false => min(stock, self.max_spend[resource].saturating_mul(remaining)),
+                };
+            }
+            next.robots[robot] += 1;
+            self.path.push((remaining, robot));
+            self.search(next);
+            self.path.pop();
+        }
+    }
+
+    /// The minutes to collect enough to build `robot`, if the current robots ever will.
+    fn wait(&self, state: &State, robot: usize) -> Option<u16> {
+        (0..self.count)
+            .map(|resource| {
+                let missing = self.costs[robot][resource].saturating_sub(state.resources[resource]);
+                match (missing, state.robots[resource]) {
+                    (0, _) => Some(0),
+                    (_, 0) => None,
+                    (missing, robots) => Some(missing.div_ceil(robots)),
+                }
+            })
+            .try_fold(0, |wait, resource_wait| Some(max(wait, resource_wait?)))
+    }
+}
+
+pub struct Day19;
+
+impl Solution for Day19 {
+    const DAY: u8 = 19;
+    const EXAMPLE: Option<&'static str> = Some(EXAMPLE);
+    type Input = Vec<Blueprint>;
+    type Part1 = u32;
+    type Part2 = u32;
+
+    fn parse(input: &str) -> Result<Self::Input> {
+        parse_input(input)
     }
-    let mut max_geode = 0;
-    let mut current_skip = skip.clone();
-    if resource.0 >= blueprint.geode.0 && resource.2 >= blueprint.geode.1 {
-        max_geode = max(max_geode, time + dfs(
-            blueprint,
-            (resource.0 + robots.0 - blueprint.geode.0, resource.1 + robots.1 , resource.2 + robots.2 - blueprint.geode.1),
-            (robots.0, robots.1, robots.2),
-            (false, false, false),
-            time - 1));
+
+    fn part1(blueprints: &Self::Input) -> Result<Self::Part1> {
+        part1(blueprints)
     }
-    if !skip.0 && robots.2 < blueprint.max_obsidian() && resource.0 >= blueprint.obsidian.0 && resource.1 >= blueprint.obsidian.1 {
-        max_geode = max(max_geode, dfs(
-            blueprint,
-            (resource.0 + robots.0 - blueprint.obsidian.0, resource.1 + robots.1 - blueprint.obsidian.1, resource.2 + robots.2),
-            (robots.0, robots.1, robots.2 + 1),
-            (false, false, false),
-            time - 1));
-        current_skip.0 = true;
+
+    fn part2(blueprints: &Self::Input) -> Result<Self::Part2> {
+        part2(blueprints)
     }
-    if !skip.1 && robots.1 < blueprint.max_clay() && resource.0 >= blueprint.clay {
-        max_geode = max(max_geode, dfs(
-            blueprint,
-            (resource.0 + robots.0 - blueprint.clay, resource.1 + robots.1, resource.2 + robots.2),
-            (robots.0, robots.1 + 1, robots.2),
-            (false, false, false),
-            time - 1));
-        current_skip.1 = true;
+}
+
+fn parse_input(input: &str) -> Result<Vec<Blueprint>> {
+    Ok(lines(input).iter().map(parse_line).collect::<Result<Vec<Blueprint>, _>>()?)
+}
+
+fn parse_line(line: &Line) -> Result<Blueprint, ParseError> {
+    let re = Regex::new(r"^Blueprint (?P<number>\d+):(?P<robots>( Each [a-z]+ robot costs \d+ [a-z]+( and \d+ [a-z]+)*\.)+)$")
+        .expect("invalid regex");
+    let caps = re.captures(line.text)
+        .ok_or_else(|| line.error(line.text, "Blueprint N: Each X robot costs N X. Each Y robot costs N X and N Y."))?;
+    let number = line.parse::<u16>(caps.name("number").unwrap().as_str(), "a blueprint number")?;
+    let robot_re = Regex::new(r"Each (?P<robot>[a-z]+) robot costs (?P<costs>[^.]+)\.").expect("invalid regex");
+    let mut resources: Vec<&str> = Vec::new();
+    let mut recipes = Vec::new();
+    for caps in robot_re.captures_iter(caps.name("robots").unwrap().as_str()) {
+        let robot = caps.name("robot").unwrap().as_str();
+        if resources.contains(&robot) {
+            return Err(line.error(robot, "a robot not described before"));
+        }
+        if resources.len() == MAX_RESOURCES {
+            return Err(line.error(robot, &format!("at most {MAX_RESOURCES} kinds of robot")));
+        }
+        resources.push(robot);
+        recipes.push(caps.name("costs").unwrap().as_str());
     }
-    if !skip.2 && robots.0 < blueprint.max_ore() && resource.0 >= blueprint.ore {
-        max_geode = max(max_geode, dfs(
-            blueprint,
-            (resource.0 + robots.0 - blueprint.ore, resource.1 + robots.1, resource.2 + robots.2),
-            (robots.0 + 1, robots.1, robots.2),
-            (false, false, false),
-            time - 1));
-        current_skip.2 = true;
+    let mut costs = vec![vec![0; resources.len()]; resources.len()];
+    for (robot, recipe) in recipes.iter().enumerate() {
+        for cost in recipe.split(" and ") {
+            let (amount, resource) = cost.split_once(' ').unwrap();
+            let index = resources.iter().position(|name| *name == resource)
+                .ok_or_else(|| line.error(resource, "a resource some robot collects"))?;
+            costs[robot][index] += line.parse::<u16>(amount, "a cost")?;
+        }
     }
-    max_geode = max(max_geode, dfs(
-        blueprint, 
-        (resource.0 + robots.0, resource.1 + robots.1, resource.2 + robots.2), 
-        robots, 
-        current_skip, 
-        time - 1));
-    max_geode
+    Ok(Blueprint { number, resources: resources.iter().map(|name| name.to_string()).collect(), costs })
+}
+
+fn part1(blueprints: &[Blueprint]) -> Result<u32> {
+    Ok(evaluate(blueprints, GEODE, 24, default_threads())?.iter()
+        .zip(blueprints)
+        .map(|(geodes, blueprint)| u32::from(blueprint.number) * u32::from(*geodes))
+        .sum())
 }
 
-fn part2(blueprints: &Vec<Blueprint>) -> u16 {
-    let answer1 = dfs(&blueprints[0], (0,0,0), (1,0,0), (false, false, false), 31);
-    let answer2 = dfs(&blueprints[1], (0,0,0), (1,0,0), (false, false, false), 31);
-    let answer3 = dfs(&blueprints[2], (0,0,0), (1,0,0), (false, false, false), 31);
-    println!("{answer1} {answer2} {answer3}");
-    answer1 * answer2 * answer3
+fn part2(blueprints: &[Blueprint]) -> Result<u32> {
+    // the elephants ate all but the first three blueprints, if there were that many
+    if blueprints.is_empty() {
+        bail!("no blueprints");
+    }
+    let first = &blueprints[..min(blueprints.len(), 3)];
+    Ok(evaluate(first, GEODE, 32, default_threads())?.iter().map(|geodes| u32::from(*geodes)).product())
 }
 
-// cargo test --bin day19 -- --nocapture
+const EXAMPLE: &str = "Blueprint 1: Each ore robot costs 4 ore. Each clay robot costs 2 ore. Each obsidian robot costs 3 ore and 14 clay. Each geode robot costs 2 ore and 7 obsidian.\n\
+                       Blueprint 2: Each ore robot costs 2 ore. Each clay robot costs 3 ore. Each obsidian robot costs 3 ore and 8 clay. Each geode robot costs 3 ore and 12 obsidian.";
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
     #[test]
     fn test1() {
-        let result = part1(&parse_input(&INPUT));
+        let result = part1(&parse_input(EXAMPLE).unwrap()).unwrap();
         assert_eq!(result, 33);
     }
 
-    const INPUT: &str ="Blueprint 1: Each ore robot costs 4 ore. Each clay robot costs 2 ore. Each obsidian robot costs 3 ore and 14 clay. Each geode robot costs 2 ore and 7 obsidian.\n\
-                        Blueprint 2: Each ore robot costs 2 ore. Each clay robot costs 3 ore. Each obsidian robot costs 3 ore and 8 clay. Each geode robot costs 3 ore and 12 obsidian.";
-}
\ No newline at end of file
+    #[test]
+    fn test2() {
+        let result = part2(&parse_input(EXAMPLE).unwrap()).unwrap();
+        assert_eq!(result, 56 * 62);
+    }
+
+    #[test]
+    fn test_max_collected() {
+        let blueprints = parse_input(EXAMPLE).unwrap();
+        let geodes: Vec<u16> = blueprints.iter().map(|blueprint| max_collected(blueprint, 3, 24)).collect();
+        assert_eq!(geodes, vec![9, 12]);
+        let geodes: Vec<u16> = blueprints.iter().map(|blueprint| max_collected(blueprint, 3, 32)).collect();
+        assert_eq!(geodes, vec![56, 62]);
+    }
+
+    #[test]
+    fn test_evaluate() {
+        let mut blueprints = parse_input(EXAMPLE).unwrap();
+        blueprints.extend(parse_input(EXAMPLE).unwrap());
+        let expected: Vec<u16> = blueprints.iter().map(|blueprint| max_collected(blueprint, 3, 24)).collect();
+        for threads in [0, 1, 3, 8] {
+            assert_eq!(evaluate(&blueprints, GEODE, 24, threads).unwrap(), expected);
+        }
+        assert!(evaluate(&[], GEODE, 24, 4).unwrap().is_empty());
+        assert!(evaluate(&blueprints, "diamond", 24, 4).is_err());
+        assert!(part2(&[]).is_err());
+    }
+
+    #[test]
+    fn test_resources() {
+        let blueprints = parse_input(EXAMPLE).unwrap();
+        assert_eq!(blueprints[0].resources(), ["ore", "clay", "obsidian", "geode"]);
+        assert_eq!(blueprints[0].costs, vec![vec![4, 0, 0, 0], vec![2, 0, 0, 0], vec![3, 14, 0, 0], vec![2, 0, 7, 0]]);
+
+        // a fifth resource that costs geodes leaves the best geode plan alone
+        let line = Line { number: 1, text: "Blueprint 1: Each ore robot costs 4 ore. Each clay robot costs 2 ore. Each obsidian robot costs 3 ore and 14 clay. Each geode robot costs 2 ore and 7 obsidian. Each diamond robot costs 2 geode." };
+        let blueprint = parse_line(&line).unwrap();
+        let diamond = blueprint.resource("diamond").unwrap();
+        assert_eq!(blueprint.cost(diamond, 3), 2);
+        assert_eq!(max_collected(&blueprint, 3, 24), 9);
+        let best = best_schedule(&blueprint, diamond, 24);
+        assert_eq!(best.collected(diamond), max_collected(&blueprint, diamond, 24));
+        assert!(best.collected(diamond) > 0);
+
+        // gems straight from ore: a gem robot in minutes 2 and 3 collects 1 + 2 gems by minute 4
+        let line = Line { number: 1, text: "Blueprint 7: Each ore robot costs 1 ore. Each gem robot costs 1 ore." };
+        let blueprint = parse_line(&line).unwrap();
+        assert_eq!(max_collected(&blueprint, 1, 4), 3);
+        assert_eq!(best_schedule(&blueprint, 1, 4).builds()[1..3], [Some(1), Some(1)]);
+    }
+
+    #[test]
+    fn test_parse_error() {
+        let error = parse_input("Blueprint 1: Each ore robot costs 4 ore. Each clay robot costs 2 sand.").unwrap_err().downcast::<ParseError>().unwrap();
+        assert_eq!((error.column, error.token.as_str()), (66, "sand"));
+        let error = parse_input("Blueprint 1: Each ore robot costs 4 ore. Each ore robot costs 2 ore.").unwrap_err().downcast::<ParseError>().unwrap();
+        assert_eq!((error.column, error.token.as_str()), (47, "ore"));
+        assert!(parse_input("Blueprint 1: Each ore robot costs 4 ore").is_err());
+    }
+
+    #[test]
+    fn test_schedule() {
+        let blueprints = parse_input(EXAMPLE).unwrap();
+        // the plan of the puzzle text for the first blueprint
+        let mut builds = vec![None; 24];
+        for (minute, robot) in [(3, 1), (5, 1), (7, 1), (11, 2), (12, 1), (15, 2), (18, 3), (21, 3)] {
+            builds[minute - 1] = Some(robot);
+        }
+        let schedule = blueprints[0].replay(&builds).unwrap();
+        assert_eq!(schedule.collected(3), 9);
+        assert_eq!(schedule.minutes[23], Minute { built: None, resources: vec![6, 41, 8, 9], robots: vec![1, 4, 2, 2] });
+        let story = schedule.to_string();
+        assert!(story.starts_with("== Minute 1 ==\n\
+                                   1 ore-collecting robot collects 1 ore; you now have 1 ore.\n\
+                                   \n\
+                                   == Minute 2 ==\n\
+                                   1 ore-collecting robot collects 1 ore; you now have 2 ore.\n\
+                                   \n\
+                                   == Minute 3 ==\n\
+                                   Spend 2 ore to start building a clay-collecting robot.\n\
+                                   1 ore-collecting robot collects 1 ore; you now have 1 ore.\n\
+                                   The new clay-collecting robot is ready; you now have 1 of them.\n"));
+        assert!(story.contains("Spend 3 ore and 14 clay to start building an obsidian-collecting robot.\n"));
+        assert!(story.ends_with("2 geode-cracking robots crack 2 geodes; you now have 9 open geodes.\n"));
+
+        for (blueprint, geodes) in blueprints.iter().zip([9, 12]) {
+            let best = best_schedule(blueprint, 3, 24);
+            assert_eq!(best.collected(3), geodes);
+            assert_eq!(blueprint.replay(&best.builds()).unwrap(), best);
+        }
+        assert!(blueprints[0].replay(&[Some(0)]).is_err());
+    }
+}
@@ -1,49 +1,74 @@
 use anyhow::Result;
-use std::fs;
+use crate::parse::{lines, Line, ParseError};
+use crate::Solution;
 use HandShape::*;
 
-#[derive(Debug)]
-enum HandShape {
+#[derive(Debug, Clone, Copy)]
+pub enum HandShape {
     Rock,
     Paper,
     Scissors
 }
 
-fn main() -> Result<()> {
-    let part1: u32 = fs::read_to_string("day2.input")?
-        .split("\n")
-        .flat_map(|line| line.split_once(" "))
-        .map(|(opponent, elf)| (parse_hand_shape(opponent), parse_hand_shape(elf)))
-        .map(|(opponent, elf)| score(opponent, elf))
-        .sum();
-    println!("{}", part1);
+pub struct Day2;
 
-    let part2: u32 = fs::read_to_string("day2.input")?
-        .split("\n")
-        .flat_map(|line| line.split_once(" "))
-        .map(|(opponent, elf)| (parse_hand_shape(opponent), derive_hand_shape(opponent, elf)))
-        .map(|(opponent, elf)| score(opponent, elf))
-        .sum();
-    println!("{}", part2);
+impl Solution for Day2 {
+    const DAY: u8 = 2;
+    const EXAMPLE: Option<&'static str> = Some(EXAMPLE);
+    type Input = Vec<(HandShape, HandShape)>;
+    type Part1 = u32;
+    type Part2 = u32;
+
+    fn parse(input: &str) -> Result<Self::Input> {
+        parse_input(input)
+    }
 
-    Ok(())
+    fn part1(rounds: &Self::Input) -> Result<Self::Part1> {
+        Ok(part1(rounds))
+    }
+
+    fn part2(rounds: &Self::Input) -> Result<Self::Part2> {
+        Ok(part2(rounds))
+    }
 }
 
-fn parse_hand_shape(string: &str) -> HandShape {
+fn parse_input(input: &str) -> Result<Vec<(HandShape, HandShape)>> {
+    let mut rounds = Vec::new();
+    for line in lines(input) {
+        let (opponent, elf) = line.split_once(" ")?;
+        rounds.push((parse_hand_shape(&line, opponent, ["A", "B", "C"])?, parse_hand_shape(&line, elf, ["X", "Y", "Z"])?));
+    }
+    Ok(rounds)
+}
+
+fn part1(rounds: &[(HandShape, HandShape)]) -> u32 {
+    rounds.iter()
+        .map(|(opponent, elf)| score(*opponent, *elf))
+        .sum()
+}
+
+fn part2(rounds: &[(HandShape, HandShape)]) -> u32 {
+    rounds.iter()
+        .map(|(opponent, elf)| (*opponent, derive_hand_shape(opponent, elf)))
+        .map(|(opponent, elf)| score(opponent, elf))
+        .sum()
+}
+
+fn parse_hand_shape(line: &Line, string: &str, codes: [&str; 3]) -> Result<HandShape, ParseError> {
     match string {
-        "A" | "X" => Rock,
-        "B" | "Y" => Paper,
-        "C" | "Z" => Scissors,
-        _ => panic!()
+        s if s == codes[0] => Ok(Rock),
+        s if s == codes[1] => Ok(Paper),
+        s if s == codes[2] => Ok(Scissors),
+        _ => Err(line.error(string, &codes.join(", "))),
     }
 }
 
-fn derive_hand_shape(opponent: &str, elf: &str) -> HandShape {
-    match (opponent, elf) {
-        ("A", "Y") | ("B", "X") | ("C", "Z") => Rock,
-        ("A", "Z") | ("B", "Y") | ("C", "X") => Paper,
-        ("A", "X") | ("B", "Z") | ("C", "Y") => Scissors,
-        _ => panic!()
+// in part 2 the second column is the outcome: Rock (X) loses, Paper (Y) draws, Scissors (Z) wins
+fn derive_hand_shape(opponent: &HandShape, outcome: &HandShape) -> HandShape {
+    match (opponent, outcome) {
+        (Rock, Paper) | (Paper, Rock) | (Scissors, Scissors) => Rock,
+        (Rock, Scissors) | (Paper, Paper) | (Scissors, Rock) => Paper,
+        (Rock, Rock) | (Paper, Scissors) | (Scissors, Paper) => Scissors,
     }
 }
 
@@ -59,4 +84,31 @@ fn score(opponent: HandShape, elf: HandShape) -> u32 {
         (Rock, Paper) | (Paper, Scissors) | (Scissors, Rock) => 6,
     };
     shape_score + round_score
-}
\ No newline at end of file
+}
+
+const EXAMPLE: &str = "A Y\n\
+                       B X\n\
+                       C Z";
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test1() {
+        let result = part1(&parse_input(EXAMPLE).unwrap());
+        assert_eq!(result, 15);
+    }
+
+    #[test]
+    fn test2() {
+        let result = part2(&parse_input(EXAMPLE).unwrap());
+        assert_eq!(result, 12);
+    }
+
+    #[test]
+    fn test_parse_error() {
+        let error = parse_input("A Y\nB Q").unwrap_err().downcast::<ParseError>().unwrap();
+        assert_eq!((error.line, error.column, error.token.as_str()), (2, 3, "Q"));
+    }
+}
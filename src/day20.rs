@@ -1,94 +1,255 @@
-use anyhow::Result;
-use std::fs;
+use anyhow::{anyhow, bail, Result};
+use crate::Solution;
+use crate::parse::lines;
 
+/// The key part 2 multiplies every number by before mixing.
+pub const DECRYPTION_KEY: i64 = 811589153;
 
-fn main() -> Result<()> {
-    let numbers: Vec<(usize, i64)> = parse_input(fs::read_to_string("day20.input")?.as_str());
-    println!("{}", part1(&numbers));
-    println!("{}", part2(&numbers));
-    Ok(())
+/// How far after the 0 the numbers making up the grove coordinates lie.
+pub const GROVE_OFFSETS: [usize; 3] = [1000, 2000, 3000];
+
+pub struct Day20;
+
+impl Solution for Day20 {
+    const DAY: u8 = 20;
+    const EXAMPLE: Option<&'static str> = Some(EXAMPLE);
+    type Input = Vec<i64>;
+    type Part1 = i64;
+    type Part2 = i64;
+
+    fn parse(input: &str) -> Result<Self::Input> {
+        parse_input(input)
+    }
+
+    fn part1(numbers: &Self::Input) -> Result<Self::Part1> {
+        part1(numbers)
+    }
+
+    fn part2(numbers: &Self::Input) -> Result<Self::Part2> {
+        part2(numbers)
+    }
+}
+
+fn parse_input(input: &str) -> Result<Vec<i64>> {
+    let numbers: Vec<i64> = lines(input).iter()
+        .map(|line| line.parse::<i64>(line.text, "an integer"))
+        .collect::<Result<Vec<i64>, _>>()?;
+    if !numbers.contains(&0) {
+        bail!("the encrypted file has no 0");
+    }
+    Ok(numbers)
 }
 
-fn parse_input(input: &str) -> Vec<(usize, i64)> {
-    input.split("\n").map(|number| number.parse::<i64>().unwrap()).collect::<Vec<i64>>()
-        .iter().enumerate().map(|(index, number)| (index, *number)).collect()
+/// The numbers of the file in their current order, as indices into the original list, kept in
+/// buckets of about √n so that finding, taking out and putting back a number costs O(√n).
+struct Mixer {
+    buckets: Vec<Vec<usize>>,
+    /// The bucket holding each number.
+    bucket_of: Vec<usize>,
+    bucket_size: usize,
+    /// Numbers moved since the buckets were last evened out.
+    moves: usize,
 }
 
-fn part1(numbers: &Vec<(usize, i64)>) -> i64 {
-    let n = numbers.len();
-    let mut mutable_numbers = numbers.clone();
-    for initial_index in 0..n {
-        let current_index = mutable_numbers.iter().enumerate().find(|(_, (index, _))| *index == initial_index).unwrap().0;
-        let step_forward = mutable_numbers[current_index].1.rem_euclid((n - 1) as i64) as usize;
-        let next_index = (current_index + step_forward) % n;
-        if current_index <= next_index {
-            move_forward(&mut mutable_numbers, current_index, next_index);
-        } else {
-            move_backward(&mut mutable_numbers, current_index, next_index);
+impl Mixer {
+    fn new(len: usize) -> Mixer {
+        let bucket_size = (len as f64).sqrt().ceil().max(1.0) as usize;
+        let mut mixer = Mixer { buckets: vec![(0..len).collect()], bucket_of: vec![0; len], bucket_size, moves: 0 };
+        mixer.rebuild();
+        mixer
+    }
+
+    /// Cuts the order into buckets of `bucket_size` again, as moves make some grow and others shrink.
+    fn rebuild(&mut self) {
+        let order: Vec<usize> = self.order().collect();
+        self.buckets = order.chunks(self.bucket_size).map(|chunk| chunk.to_vec()).collect();
+        for (bucket, numbers) in self.buckets.iter().enumerate() {
+            for index in numbers {
+                self.bucket_of[*index] = bucket;
+            }
         }
+        self.moves = 0;
     }
 
-    let zero_index = mutable_numbers.iter().enumerate().find(|(_, (_, value))| *value == 0).unwrap().0;
-    mutable_numbers[(zero_index + 1000) % n].1 + mutable_numbers[(zero_index + 2000) % n].1 + mutable_numbers[(zero_index + 3000) % n].1
-}
+    fn order(&self) -> impl Iterator<Item = usize> + '_ {
+        self.buckets.iter().flatten().copied()
+    }
+
+    /// Takes number `index` out of the order and returns the position it had.
+    fn remove(&mut self, index: usize) -> usize {
+        let bucket = self.bucket_of[index];
+        let offset = self.buckets[bucket].iter().position(|number| *number == index).unwrap();
+        self.buckets[bucket].remove(offset);
+        self.buckets[..bucket].iter().map(|numbers| numbers.len()).sum::<usize>() + offset
+    }
+
+    /// Puts number `index` back so that `position` numbers come before it.
+    fn insert(&mut self, mut position: usize, index: usize) {
+        let mut bucket = 0;
+        while bucket + 1 < self.buckets.len() && position > self.buckets[bucket].len() {
+            position -= self.buckets[bucket].len();
+            bucket += 1;
+        }
+        self.buckets[bucket].insert(position, index);
+        self.bucket_of[index] = bucket;
+        self.moves += 1;
+        if self.moves >= self.bucket_size {
+            self.rebuild();
+        }
+    }
 
-fn move_forward(array: &mut Vec<(usize, i64)>, index: usize, next_index: usize) -> () {
-    let first = array[index].clone();
-    for i in index..next_index {
-        array[i] = array[i + 1];
+    /// Moves number `index` forward by `step` places around the circle of the other numbers.
+    /// A number that wraps around past the end lands just after the start, and one that comes to
+    /// rest exactly at the end stays there.
+    fn shift(&mut self, index: usize, step: i64) {
+        let others = self.bucket_of.len() - 1;
+        if others == 0 {
+            return;
+        }
+        let position = self.remove(index);
+        let target = position + step.rem_euclid(others as i64) as usize;
+        self.insert(if target > others { target - others } else { target }, index);
     }
-    array[next_index] = first;
 }
 
-fn move_backward(array: &mut Vec<(usize, i64)>, index: usize, previous_index: usize) -> () {
-    let last = array[index].clone();
-    for i in ((previous_index + 1)..index).rev() {
-        array[i + 1] = array[i];
+/// An encrypted file being mixed one round at a time.
+pub struct Mixing {
+    numbers: Vec<i64>,
+    mixer: Mixer,
+    rounds: usize,
+}
+
+impl Mixing {
+    /// The file with every number multiplied by `key`, not yet mixed.
+    pub fn new(numbers: &[i64], key: i64) -> Result<Mixing> {
+        let numbers = numbers.iter()
+            .map(|number| number.checked_mul(key).ok_or_else(|| anyhow!("{number} times the key {key} overflows")))
+            .collect::<Result<Vec<i64>>>()?;
+        Ok(Mixing { mixer: Mixer::new(numbers.len()), numbers, rounds: 0 })
+    }
+
+    /// Moves every number once, in the order of the original file.
+    pub fn round(&mut self) {
+        for (index, number) in self.numbers.iter().enumerate() {
+            self.mixer.shift(index, *number);
+        }
+        self.rounds += 1;
+    }
+
+    /// The rounds mixed so far.
+    pub fn rounds(&self) -> usize {
+        self.rounds
+    }
+
+    /// The numbers in their current order.
+    pub fn sequence(&self) -> Vec<i64> {
+        self.mixer.order().map(|index| self.numbers[index]).collect()
+    }
+
+    /// The sum of the numbers `offsets` places after the 0, wrapping around the file.
+    pub fn grove_coordinates(&self, offsets: &[usize]) -> Result<i64> {
+        let sequence = self.sequence();
+        let Some(zero) = sequence.iter().position(|number| *number == 0) else { bail!("the file has no 0") };
+        offsets.iter()
+            .try_fold(0i64, |sum, offset| sum.checked_add(sequence[(zero + offset) % sequence.len()]))
+            .ok_or_else(|| anyhow!("the grove coordinates overflow"))
     }
-    array[previous_index + 1] = last;
 }
 
-fn part2(numbers: &Vec<(usize, i64)>) -> i64 {
-    let n = numbers.len();
-    let mut mutable_numbers: Vec<(usize, i64)> = numbers.iter().map(|(index, number)| (index.clone(), number * 811589153)).collect();
-    for _ in 0..10 {
-        for initial_index in 0..n {
-            let current_index = mutable_numbers.iter().enumerate().find(|(_, (index, _))| *index == initial_index).unwrap().0;
-            let step_forward = mutable_numbers[current_index].1.rem_euclid((n - 1) as i64) as usize;
-            let next_index = (current_index + step_forward) % n;
-            if current_index <= next_index {
-                move_forward(&mut mutable_numbers, current_index, next_index);
-            } else {
-                move_backward(&mut mutable_numbers, current_index, next_index);
-            }
-        }    
+/// Multiplies the numbers by `key` and mixes them `rounds` times.
+pub fn mix(numbers: &[i64], key: i64, rounds: usize) -> Result<Mixing> {
+    let mut mixing = Mixing::new(numbers, key)?;
+    for _ in 0..rounds {
+        mixing.round();
     }
-    let zero_index = mutable_numbers.iter().enumerate().find(|(_, (_, value))| *value == 0).unwrap().0;
-    mutable_numbers[(zero_index + 1000) % n].1 + mutable_numbers[(zero_index + 2000) % n].1 + mutable_numbers[(zero_index + 3000) % n].1
+    Ok(mixing)
 }
 
-// cargo test --bin day17 -- --nocapture
+fn part1(numbers: &[i64]) -> Result<i64> {
+    mix(numbers, 1, 1)?.grove_coordinates(&GROVE_OFFSETS)
+}
+
+fn part2(numbers: &[i64]) -> Result<i64> {
+    mix(numbers, DECRYPTION_KEY, 10)?.grove_coordinates(&GROVE_OFFSETS)
+}
+
+const EXAMPLE: &str = "1\n\
+                       2\n\
+                       -3\n\
+                       3\n\
+                       -2\n\
+                       0\n\
+                       4";
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
     #[test]
     fn test1() {
-        let result = part1(&parse_input(&INPUT));
+        let result = part1(&parse_input(EXAMPLE).unwrap()).unwrap();
         assert_eq!(result, 3);
     }
 
     #[test]
     fn test2() {
-        let result = part2(&parse_input(&INPUT));
+        let result = part2(&parse_input(EXAMPLE).unwrap()).unwrap();
         assert_eq!(result, 1623178306);
     }
 
-    const INPUT: &str ="1\n\
-                        2\n\
-                        -3\n\
-                        3\n\
-                        -2\n\
-                        0\n\
-                        4";
+    #[test]
+    fn test_mixer() {
+        // the same moves made one by one on a plain list
+        fn mix_slowly(numbers: &[i64], rounds: usize) -> Vec<i64> {
+            let mut order: Vec<usize> = (0..numbers.len()).collect();
+            for _ in 0..rounds {
+                for (index, number) in numbers.iter().enumerate() {
+                    let position = order.iter().position(|other| *other == index).unwrap();
+                    order.remove(position);
+                    let others = order.len();
+                    let target = position + number.rem_euclid(others as i64) as usize;
+                    order.insert(if target > others { target - others } else { target }, index);
+                }
+            }
+            order.iter().map(|index| numbers[*index]).collect()
+        }
+
+        let mut seed: i64 = 7;
+        let numbers: Vec<i64> = (0..500)
+            .map(|_| {
+                seed = (seed * 1103515245 + 12345) % 2147483648;
+                seed % 20001 - 10000
+            })
+            .collect();
+        assert_eq!(mix(&numbers, 1, 3).unwrap().sequence(), mix_slowly(&numbers, 3));
+        assert_eq!(mix(&[0], 1, 1).unwrap().sequence(), vec![0]);
+        assert_eq!(mix(&[1, 0], 1, 1).unwrap().sequence(), vec![1, 0]);
+    }
+
+    #[test]
+    fn test_rounds() {
+        let numbers = parse_input(EXAMPLE).unwrap();
+        assert_eq!(mix(&numbers, 1, 1).unwrap().sequence(), vec![1, 2, -3, 4, 0, 3, -2]);
+        // the arrangements after each round of the worked example of part 2
+        let mut mixing = Mixing::new(&numbers, DECRYPTION_KEY).unwrap();
+        assert_eq!(mixing.sequence(), vec![811589153, 1623178306, -2434767459, 2434767459, -1623178306, 0, 3246356612]);
+        let rounds = [
+            vec![0, -2434767459, 3246356612, -1623178306, 2434767459, 1623178306, 811589153],
+            vec![0, 2434767459, 1623178306, 3246356612, -2434767459, -1623178306, 811589153],
+            vec![0, 811589153, 2434767459, 3246356612, 1623178306, -1623178306, -2434767459],
+        ];
+        for expected in rounds {
+            mixing.round();
+            assert_eq!(mixing.sequence(), expected);
+        }
+        while mixing.rounds() < 10 {
+            mixing.round();
+        }
+        assert_eq!(mixing.sequence(), vec![0, -2434767459, 1623178306, 3246356612, -1623178306, 2434767459, 811589153]);
+        assert_eq!(mixing.grove_coordinates(&[1000]).unwrap(), 811589153);
+        assert_eq!(mixing.grove_coordinates(&GROVE_OFFSETS).unwrap(), 1623178306);
+        assert!(mix(&[1, 2], 1, 1).unwrap().grove_coordinates(&GROVE_OFFSETS).is_err());
+        assert!(mix(&[0, i64::MAX / 2], 3, 1).is_err());
+        assert!(mix(&[0, i64::MAX, 1], 1, 0).unwrap().grove_coordinates(&GROVE_OFFSETS).is_err());
+    }
 }
\ No newline at end of file
@@ -1,153 +1,270 @@
-use anyhow::Result;
-use std::fs;
-use std::collections::HashMap;
+use anyhow::{anyhow, bail, Result};
+use crate::Solution;
+use crate::parse::{lines, Line, ParseError};
+use num::rational::Ratio;
+use num::traits::{CheckedAdd, CheckedDiv, CheckedMul, CheckedSub};
+use num::ToPrimitive;
+use std::collections::{HashMap, HashSet};
 use Expression::*;
 
 #[derive(Debug, Clone, Eq, Hash, PartialEq)]
-enum Operator {
+pub enum Operator {
     Add,
     Minus,
     Multiply,
     Divide,
 }
 
-#[derive(Debug, Eq, Hash, PartialEq)]
-enum Expression {
-    Number(i64),
+impl Operator {
+    /// `number1 operator number2`, unless it divides by zero or overflows.
+    fn apply(&self, number1: &Rational, number2: &Rational) -> Result<Rational> {
+        let (result, symbol) = match self {
+            Operator::Add => (number1.checked_add(number2), '+'),
+            Operator::Minus => (number1.checked_sub(number2), '-'),
+            Operator::Multiply => (number1.checked_mul(number2), '*'),
+            Operator::Divide => (number1.checked_div(number2), '/'),
+        };
+        result.ok_or_else(|| anyhow!("{number1} {symbol} {number2} divides by zero or overflows"))
+    }
+}
+
+/// Numbers are exact fractions, so that no division along the way loses anything.
+pub type Rational = Ratio<i128>;
+
+#[derive(Debug, Clone, Eq, Hash, PartialEq)]
+pub enum Expression {
+    Number(Rational),
     Variable(String),
     Arithmetic(Box<Expression>, Box<Expression>, Operator),
 }
 
 impl Expression {
-    fn get_number(&self) -> i64 {
+    pub fn get_number(&self) -> Result<Rational> {
         match &self {
-            Number(number) => *number,
-            _ => panic!(),
+            Number(number) => Ok(*number),
+            _ => bail!("{self:?} does not come down to a number"),
         }
     }
 }
 
-fn main() -> Result<()> {
-    let mut assignments: HashMap<Expression, Expression> = parse_input(fs::read_to_string("day21.input")?.as_str());
-    println!("{}", part1(&assignments));
-    println!("{}", part2(&mut assignments));
-    Ok(())
+pub struct Day21;
+
+impl Solution for Day21 {
+    const DAY: u8 = 21;
+    const EXAMPLE: Option<&'static str> = Some(EXAMPLE);
+    type Input = HashMap<Expression, Expression>;
+    type Part1 = i64;
+    type Part2 = i64;
+
+    fn parse(input: &str) -> Result<Self::Input> {
+        parse_input(input)
+    }
+
+    fn part1(assignments: &Self::Input) -> Result<Self::Part1> {
+        part1(assignments)
+    }
+
+    fn part2(assignments: &Self::Input) -> Result<Self::Part2> {
+        part2(&mut assignments.clone())
+    }
+}
+
+fn parse_input(input: &str) -> Result<HashMap<Expression, Expression>> {
+    let lines = lines(input);
+    let parsed = lines.iter().map(parse_line).collect::<Result<Vec<(Expression, Expression)>, _>>()?;
+    let assignments: HashMap<Expression, Expression> = parsed.iter().cloned().collect();
+    let (mut visiting, mut done) = (HashSet::new(), HashSet::new());
+    for (monkey, _) in &parsed {
+        if let Some(waiting) = find_cycle(&assignments, monkey, &mut visiting, &mut done) {
+            let (line, _) = lines.iter().zip(&parsed).find(|(_, (other, _))| other == waiting).expect("every monkey in a cycle has a line");
+            let (name, _) = line.split_once(": ")?;
+            bail!(line.error(name, "a monkey that does not end up waiting on itself"));
+        }
+    }
+    Ok(assignments)
 }
 
-fn parse_input(input: &str) -> HashMap<Expression, Expression> {
-    input.split("\n").map(|s| parse_line(s)).collect()
+/// A monkey that ends up waiting on itself while evaluating `monkey`, if there is one.
+fn find_cycle<'a>(assignments: &'a HashMap<Expression, Expression>, monkey: &'a Expression,
+                  visiting: &mut HashSet<&'a Expression>, done: &mut HashSet<&'a Expression>) -> Option<&'a Expression> {
+    if done.contains(monkey) {
+        return None;
+    }
+    if !visiting.insert(monkey) {
+        return Some(monkey);
+    }
+    let cycle = match assignments.get(monkey) {
+        Some(Arithmetic(left, right, _)) => find_cycle(assignments, left, visiting, done)
+            .or_else(|| find_cycle(assignments, right, visiting, done)),
+        _ => None,
+    };
+    visiting.remove(monkey);
+    done.insert(monkey);
+    cycle
 }
 
-fn parse_line(s: &str) -> (Expression, Expression) {
-    let (variable, exp_string) = s.split_once(": ").unwrap();
+fn parse_line(line: &Line) -> Result<(Expression, Expression), ParseError> {
+    let (variable, exp_string) = line.split_once(": ")?;
     let expression = match exp_string.parse::<i64>() {
-        Ok(number) => Number(number),
+        Ok(number) => Number(Rational::from_integer(number.into())),
         Err(_) => {
             let splits: Vec<&str> = exp_string.split(" ").collect();
+            if splits.len() != 3 {
+                return Err(line.error(exp_string, "a number or \"<monkey> <operator> <monkey>\""));
+            }
             let operator = match splits[1] {
                 "+" => Operator::Add,
                 "-" => Operator::Minus,
                 "*" => Operator::Multiply,
                 "/" => Operator::Divide,
-                _ => panic!(),
+                token => return Err(line.error(token, "one of +, -, *, /")),
             };
             Arithmetic(Box::new(Variable(splits[0].to_string())), Box::new(Variable(splits[2].to_string())), operator)
         },
     };
-    (Variable(variable.to_string()), expression)
+    Ok((Variable(variable.to_string()), expression))
+}
+
+fn part1(assignments: &HashMap<Expression, Expression>) -> Result<i64> {
+    integer(eval(assignments, &Variable(String::from("root")))?.get_number()?, "root")
 }
 
-fn part1(assignments: &HashMap<Expression, Expression>) -> i64 {
-    eval(assignments, &Variable(String::from("root"))).get_number()
+/// The number a monkey yells, if it is a whole one that fits an `i64`.
+fn integer(number: Rational, monkey: &str) -> Result<i64> {
+    number.is_integer().then(|| number.to_integer().to_i64()).flatten()
+        .ok_or_else(|| anyhow!("{monkey} would have to yell {number}, which is not a 64-bit integer"))
 }
 
-fn eval(assignments: &HashMap<Expression, Expression>, expression: &Expression) -> Expression {
-    match expression {
+pub fn eval(assignments: &HashMap<Expression, Expression>, expression: &Expression) -> Result<Expression> {
+    Ok(match expression {
         Number(number) => Number(*number),
         Variable(variable) => match assignments.get(expression)  {
-                Some(exp) => eval(assignments, exp),
+                Some(exp) => eval(assignments, exp)?,
                 None => Variable(variable.to_string())
             },
         Arithmetic(boxed_expression1, boxed_expression2, operator) => {
-            let expression1 = eval(assignments, &**boxed_expression1);
-            let expression2 = eval(assignments, &**boxed_expression2);
-            match (&expression1, &expression2, operator) {
-                (Number(number1), Number(number2), Operator::Add) => Number(number1 + number2),
-                (Number(number1), Number(number2), Operator::Minus) => Number(number1 - number2),
-                (Number(number1), Number(number2), Operator::Multiply) => Number(number1 * number2),
-                (Number(number1), Number(number2), Operator::Divide) => Number(number1 / number2),
+            let expression1 = eval(assignments, boxed_expression1)?;
+            let expression2 = eval(assignments, boxed_expression2)?;
+            match (&expression1, &expression2) {
+                (Number(number1), Number(number2)) => Number(operator.apply(number1, number2)?),
                 _ => Arithmetic(Box::new(expression1), Box::new(expression2), operator.clone()),
             }
         },
-    }
+    })
 }
 
-fn part2(assignments: &mut HashMap<Expression, Expression>) -> i64 {
+fn part2(assignments: &mut HashMap<Expression, Expression>) -> Result<i64> {
     assignments.remove(&Variable(String::from("humn")));
-    let root_assignment = assignments.get(&Variable(String::from("root"))).unwrap();                        
-    let (expression1, expression2) = match root_assignment {
-        Arithmetic(boxed_expression1, boxed_expression2, _) => (&**boxed_expression1, &**boxed_expression2),
-        _ => panic!(),
+    let (expression1, expression2) = match assignments.get(&Variable(String::from("root"))) {
+        Some(Arithmetic(boxed_expression1, boxed_expression2, _)) => (&**boxed_expression1, &**boxed_expression2),
+        Some(_) => bail!("root does not compare two monkeys"),
+        None => bail!("there is no root monkey"),
     };
-    solve(&eval(assignments, expression1), &eval(assignments, expression2))
+    integer(solve(&eval(assignments, expression1)?, &eval(assignments, expression2)?)?, "humn")
 }
 
-fn solve(left: &Expression, right: &Expression) -> i64 {
+/// The value of `humn` that makes both sides equal, where one side is a number and the other
+/// an expression with `humn` in it exactly once.
+pub fn solve(left: &Expression, right: &Expression) -> Result<Rational> {
     match (left, right) {
-        (Arithmetic(boxed_expression1, boxed_expression2, operator), Number(number)) => 
+        (Arithmetic(boxed_expression1, boxed_expression2, operator), Number(number)) =>
             match (&**boxed_expression1, &**boxed_expression2) {
-                (_, Number(inner)) => 
+                (_, Number(inner)) =>
                     match operator {
-                        Operator::Add => solve(&**boxed_expression1, &Number(number - inner)),
-                        Operator::Minus => solve(&**boxed_expression1, &Number(number + inner)),
-                        Operator::Multiply => solve(&**boxed_expression1, &Number(number / inner)),
-                        Operator::Divide => solve(&**boxed_expression1, &Number(number * inner)),
+                        Operator::Add => solve(boxed_expression1, &Number(Operator::Minus.apply(number, inner)?)),
+                        Operator::Minus => solve(boxed_expression1, &Number(Operator::Add.apply(number, inner)?)),
+                        Operator::Multiply => solve(boxed_expression1, &Number(Operator::Divide.apply(number, inner)?)),
+                        Operator::Divide if inner.numer() == &0 => bail!("humn is divided by zero"),
+                        Operator::Divide => solve(boxed_expression1, &Number(Operator::Multiply.apply(number, inner)?)),
                     }
                 (Number(inner), _) => {
                     match operator {
-                        Operator::Add => solve(&**boxed_expression2, &Number(number - inner)),
-                        Operator::Minus => solve(&**boxed_expression2, &Number(inner - number)),
-                        Operator::Multiply => solve(&**boxed_expression2, &Number(number / inner)),
-                        Operator::Divide => solve(&**boxed_expression2, &Number(inner / number)),
+                        Operator::Add => solve(boxed_expression2, &Number(Operator::Minus.apply(number, inner)?)),
+                        Operator::Minus => solve(boxed_expression2, &Number(Operator::Minus.apply(inner, number)?)),
+                        Operator::Multiply => solve(boxed_expression2, &Number(Operator::Divide.apply(number, inner)?)),
+                        Operator::Divide => solve(boxed_expression2, &Number(Operator::Divide.apply(inner, number)?)),
                     }
                 },
-                _ => {println!("{:?}", left); todo!()},
+                _ => bail!("humn appears more than once on one side of root"),
             },
-        (Variable(_), Number(number)) => *number,
-        _ => solve(right, left),
+        (Variable(_), Number(number)) => Ok(*number),
+        (Number(_), Number(_)) => bail!("neither side of root depends on humn"),
+        (Number(_), _) => solve(right, left),
+        _ => bail!("humn appears on both sides of root"),
     }
 }
 
-// cargo test --bin 21 -- --nocapture
+const EXAMPLE: &str = "root: pppw + sjmn\n\
+                       dbpl: 5\n\
+                       cczh: sllz + lgvd\n\
+                       zczc: 2\n\
+                       ptdq: humn - dvpt\n\
+                       dvpt: 3\n\
+                       lfqf: 4\n\
+                       humn: 5\n\
+                       ljgn: 2\n\
+                       sjmn: drzm * dbpl\n\
+                       sllz: 4\n\
+                       pppw: cczh / lfqf\n\
+                       lgvd: ljgn * ptdq\n\
+                       drzm: hmdt - zczc\n\
+                       hmdt: 32";
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
     #[test]
     fn test1() {
-        let result = part1(&parse_input(&INPUT));
+        let result = part1(&parse_input(EXAMPLE).unwrap()).unwrap();
         assert_eq!(result, 152);
     }
 
     #[test]
     fn test2() {
-        let result = part2(&mut parse_input(&INPUT));
+        let result = part2(&mut parse_input(EXAMPLE).unwrap()).unwrap();
         assert_eq!(result, 301);
     }
 
-    const INPUT: &str ="root: pppw + sjmn\n\
-                        dbpl: 5\n\
-                        cczh: sllz + lgvd\n\
-                        zczc: 2\n\
-                        ptdq: humn - dvpt\n\
-                        dvpt: 3\n\
-                        lfqf: 4\n\
-                        humn: 5\n\
-                        ljgn: 2\n\
-                        sjmn: drzm * dbpl\n\
-                        sllz: 4\n\
-                        pppw: cczh / lfqf\n\
-                        lgvd: ljgn * ptdq\n\
-                        drzm: hmdt - zczc\n\
-                        hmdt: 32";                
+    #[test]
+    fn test_fractions() {
+        // 3 / 2 only makes a whole number again once it is doubled
+        let input = "root: half * humn\nhalf: three / two\nthree: 3\ntwo: 2\nhumn: 2";
+        assert_eq!(part1(&parse_input(input).unwrap()).unwrap(), 3);
+        assert!(part1(&parse_input("root: three / two\nthree: 3\ntwo: 2").unwrap()).is_err());
+
+        // humn * 2 = 7 * 3 / 6 needs humn = 7 / 4
+        let input = "root: double + sevenths\ndouble: humn * two\nsevenths: product / six\nproduct: seven * three\n\
+                     two: 2\nthree: 3\nsix: 6\nseven: 7\nhumn: 1";
+        assert!(part2(&mut parse_input(input).unwrap()).is_err());
+        // humn / 4 = 3 / 2 needs humn = 6, though halving 3 is inexact
+        let input = "root: quarter + half\nquarter: humn / four\nhalf: three / two\nfour: 4\nthree: 3\ntwo: 2\nhumn: 1";
+        assert_eq!(part2(&mut parse_input(input).unwrap()).unwrap(), 6);
+    }
+
+    #[test]
+    fn test_unsolvable() {
+        let errors = [
+            "root: square + four\nsquare: humn * humn\nfour: 4\nhumn: 1",
+            "root: left + right\nleft: humn * two\nright: humn + two\ntwo: 2\nhumn: 1",
+            "root: two + four\ntwo: 2\nfour: 4\nhumn: 1",
+            "root: scaled + four\nscaled: humn * zero\nzero: 0\nfour: 4\nhumn: 1",
+            "root: scaled + four\nscaled: humn / zero\nzero: 0\nfour: 4\nhumn: 1",
+            "root: scaled + zero\nscaled: four / humn\nzero: 0\nfour: 4\nhumn: 1",
+            "four: 4\nhumn: 1",
+        ];
+        for input in errors {
+            assert!(part2(&mut parse_input(input).unwrap()).is_err(), "{input}");
+        }
+        assert!(part1(&parse_input("root: four / zero\nfour: 4\nzero: 0").unwrap()).is_err());
+        assert!(part1(&parse_input("root: four / humn\nfour: 4").unwrap()).is_err());
+    }
+
+    #[test]
+    fn test_cycle() {
+        let error = parse_input("root: left + four\nfour: 4\nleft: right * four\nright: left - four")
+            .unwrap_err().downcast::<ParseError>().unwrap();
+        assert_eq!((error.line, error.token.as_str()), (3, "left"));
+        assert!(parse_input("root: root + root").is_err());
+    }
 }
\ No newline at end of file
@@ -1,11 +1,15 @@
-use anyhow::Result;
-use std::fs;
+use anyhow::{anyhow, bail, Result};
+use crate::Solution;
+use crate::geometry::{Direction, Rotation};
+use crate::grid::Grid;
+use crate::parse::{blocks, Line, ParseError};
+use crate::search::bfs;
 use num::integer::gcd;
 
 #[derive(Debug)]
-struct Map {
+pub struct Map {
     faces: Vec<Face>,
-    layout: Vec<Vec<usize>>,
+    layout: Grid<usize>,
 }
 
 impl Map {
@@ -17,18 +21,18 @@ impl Map {
 #[derive(Debug)]
 struct Face {
     id: usize,
-    map: Vec<Vec<Tile>>,
+    map: Grid<Tile>,
     offsets: (usize, usize),
 }
 
 #[derive(Debug)]
-struct Teleport {
+pub struct Teleport {
     edges: Vec<(usize, Direction, usize, Direction)>,
     size: usize,
 }
 
 impl Teleport {
-    fn transport(&self, face_id: usize, current_dir: &Direction, x: usize, y: usize) ->  (usize, Direction, usize, usize) {
+    pub fn transport(&self, face_id: usize, current_dir: &Direction, x: usize, y: usize) ->  (usize, Direction, usize, usize) {
         let (next_face_id, next_dir) = self.edges.iter().find(|(id, dir, _, _ )| *id == face_id && dir == current_dir).map(|(_, _, id, dir)| (id, dir)).unwrap();
         match (current_dir, next_dir) {
             (Direction::Left, Direction::Right) => (*next_face_id, Direction::Left, x, self.size - 1),
@@ -57,184 +61,100 @@ enum Tile {
     Wall,
 }
 
-#[derive(Debug, Clone, Copy)]
-enum Rotation {
-    None,
-    Clockwise,
-    Upsidedown,
-    CounterClockwise,
-}
-
-impl Rotation {
-    fn reverse(&self) -> Rotation {
-        match self {
-            Rotation::None => Rotation::None,
-            Rotation::Clockwise => Rotation::CounterClockwise,
-            Rotation::Upsidedown => Rotation::Upsidedown,
-            Rotation::CounterClockwise => Rotation::Clockwise,
-        }
-    }
-
-    fn rotate(&self, dir: &Direction) -> Direction {
-        match self {
-            Rotation::None => 
-                match dir {
-                    Direction::Left => Direction::Left,
-                    Direction::Up => Direction::Up,
-                    Direction::Right => Direction::Right,
-                    Direction::Down => Direction::Down,  
-                },
-            Rotation::Clockwise => 
-                match dir {
-                    Direction::Left => Direction::Up,
-                    Direction::Up => Direction::Right,
-                    Direction::Right => Direction::Down,
-                    Direction::Down => Direction::Left,  
-                },
-            Rotation::Upsidedown => 
-                match dir {
-                    Direction::Left => Direction::Right,
-                    Direction::Up => Direction::Down,
-                    Direction::Right => Direction::Left,
-                    Direction::Down => Direction::Up,  
-                },
-            Rotation::CounterClockwise => 
-                match dir {
-                    Direction::Left => Direction::Down,
-                    Direction::Right => Direction::Up,
-                    Direction::Up => Direction::Left,
-                    Direction::Down => Direction::Right,  
-                },
-        }
-    }
-
-    fn rotation(from: &Direction, to: &Direction) -> Rotation {
-        match (from, to) {
-            (Direction::Left, Direction::Left) => Rotation::None,
-            (Direction::Left, Direction::Up) => Rotation::Clockwise,
-            (Direction::Left, Direction::Right) => Rotation::Upsidedown,
-            (Direction::Left, Direction::Down) => Rotation::CounterClockwise,
-            (Direction::Up, Direction::Up) => Rotation::None,
-            (Direction::Up, Direction::Right) => Rotation::Clockwise,
-            (Direction::Up, Direction::Down) => Rotation::Upsidedown,
-            (Direction::Up, Direction::Left) => Rotation::CounterClockwise,
-            (Direction::Right, Direction::Right) => Rotation::None,
-            (Direction::Right, Direction::Down) => Rotation::Clockwise,
-            (Direction::Right, Direction::Left) => Rotation::Upsidedown,
-            (Direction::Right, Direction::Up) => Rotation::CounterClockwise,
-            (Direction::Down, Direction::Down) => Rotation::None,
-            (Direction::Down, Direction::Left) => Rotation::Clockwise,
-            (Direction::Down, Direction::Up) => Rotation::Upsidedown,
-            (Direction::Down, Direction::Right) => Rotation::CounterClockwise,
-        }
-    }
-}
-
 #[derive(Debug)]
-enum Instruction {
+pub enum Instruction {
     Rotate(Rotation),
     Forward(usize),
 }
 
-#[derive(Debug, PartialEq, Clone, Copy)]
-enum Direction {
-    Left,
-    Right,
-    Up,
-    Down,
+/// The next cell in `direction`, wrapping around the edges of a grid of `size` rows and columns.
+fn move_around(direction: Direction, position: (usize, usize), size: (usize, usize)) -> (usize, usize) {
+    match direction {
+        Direction::Left => (position.0, (position.1 + size.1 - 1) % size.1),
+        Direction::Right => (position.0, (position.1 + 1) % size.1),
+        Direction::Up => ((position.0 + size.0 - 1) % size.0, position.1),
+        Direction::Down => ((position.0 + 1) % size.0, position.1),
+    }
 }
 
-impl Direction {
-    fn move_from(&self, position: (usize, usize), size: (usize, usize)) -> Option<(usize, usize)> {
-        match self {
-            Direction::Left => if position.1 > 0 { Some((position.0, position.1 - 1)) } else { None },
-            Direction::Right => if position.1 + 1 < size.1 { Some((position.0, position.1 + 1)) } else { None },
-            Direction::Up => if position.0 > 0 { Some((position.0 - 1, position.1)) } else { None },
-            Direction::Down => if position.0 + 1 < size.0 { Some((position.0 + 1, position.1)) } else { None },  
-        }
+fn facing_score(direction: Direction) -> usize {
+    match direction {
+        Direction::Right => 0,
+        Direction::Down => 1,
+        Direction::Left => 2,
+        Direction::Up => 3,
     }
+}
 
-    fn move_around(&self, position: (usize, usize), size: (usize, usize)) -> (usize, usize) {
-        match self {
-            Direction::Left => (position.0, (position.1 + size.1 - 1) % size.1),
-            Direction::Right => (position.0, (position.1 + 1) % size.1),
-            Direction::Up => ((position.0 + size.0 - 1) % size.0, position.1),
-            Direction::Down => ((position.0 + 1) % size.0, position.1),  
-        }
-    }
+pub struct Day22;
 
-    fn score(&self) -> usize {
-        match self {
-            Direction::Left => 2,
-            Direction::Right => 0,
-            Direction::Up => 3,
-            Direction::Down => 1,  
-        }  
-    }
+impl Solution for Day22 {
+    const DAY: u8 = 22;
+    const EXAMPLE: Option<&'static str> = Some(EXAMPLE);
+    type Input = (Map, Vec<Instruction>);
+    type Part1 = usize;
+    type Part2 = usize;
 
-    fn opposite(&self) -> Direction {
-        match self {
-            Direction::Left => Direction::Right,
-            Direction::Right => Direction::Left,
-            Direction::Up => Direction::Down,
-            Direction::Down => Direction::Up,  
-        }   
+    fn parse(input: &str) -> Result<Self::Input> {
+        parse_input(input)
     }
 
-    fn all_directions() -> Vec<Direction> {
-        vec![Direction::Left, Direction::Right, Direction::Up, Direction::Down]
+    fn part1(input: &Self::Input) -> Result<Self::Part1> {
+        let (map, path) = input;
+        Ok(part1(map, path))
     }
-}
 
-fn main() -> Result<()> {
-    let (map, path): (Map, Vec<Instruction>) = parse_input(fs::read_to_string("day22.input")?.as_str());
-    println!("{}", part1(&map, &path));
-    println!("{}", part2(&map, &path));
-    Ok(())
+    fn part2(input: &Self::Input) -> Result<Self::Part2> {
+        let (map, path) = input;
+        part2(map, path)
+    }
 }
 
-fn parse_input(input: &str) -> (Map, Vec<Instruction>) {
-    input.split_once("\n\n")
-        .map(|(map_str, path_str)| (parse_map(map_str), parse_instruction(path_str)))
-        .unwrap()
+fn parse_input(input: &str) -> Result<(Map, Vec<Instruction>)> {
+    match &blocks(input)[..] {
+        [map, path] if path.len() == 1 => Ok((parse_map(map)?, parse_instruction(&path[0])?)),
+        _ => Err(anyhow!("expected a map and a path separated by a blank line")),
+    }
 }
 
-fn parse_map(s: &str) -> Map {
-    let raw_map: Vec<Vec<char>> = s.split("\n").map(|row| row.chars().collect::<Vec<char>>()).collect();
-    let width = raw_map.iter().map(|v| v.len()).max().unwrap();
-    let height = raw_map.len();
+fn parse_map(rows: &[Line]) -> Result<Map, ParseError> {
+    let raw_map: Grid<char> = Grid::from_lines_padded(rows, ' ', |c| matches!(c, ' ' | '.' | '#').then_some(c), "' ', '.' or '#'")?;
+    let width = raw_map.columns();
+    let height = raw_map.rows();
     let size = gcd(width, height);
     let n = width / size;
     let m = height / size;
     let mut faces: Vec<Face> = Vec::new();
-    let mut layout = vec![vec![0; n]; m];
+    let mut layout = Grid::new(m, n, 0);
     let mut face_id = 0;
     for i in 0..m {
         for j in 0..n {
-            if j * size < raw_map[i * size].len() && raw_map[i * size][j * size] != ' ' {
-                let mut map = vec![vec![Tile::Open; size]; size];
-                for x in 0..size {
-                    for y in 0..size {
-                        map[x][y] = match raw_map[i * size + x][j * size + y] {
-                            '.' => Tile::Open,
-                            '#' => Tile::Wall,
-                            _ => panic!(),
-                        };
-                    }
+            let face = raw_map.crop((i * size, j * size), size, size);
+            if raw_map[(i * size, j * size)] == ' ' {
+                if let Some(((x, y), c)) = face.iter().find(|(_, c)| **c != ' ') {
+                    return Err(rows[i * size + x].error_at(j * size + y + 1, &c.to_string(), "' ' around the faces"));
                 }
+            } else {
+                if let Some(((x, y), c)) = face.iter().find(|(_, c)| **c == ' ') {
+                    return Err(rows[i * size + x].error_at(j * size + y + 1, &c.to_string(), "'.' or '#'"));
+                }
+                let map = face.map(|c| if *c == '#' { Tile::Wall } else { Tile::Open });
                 face_id += 1;
-                faces.push(Face { map: map, offsets: (i, j), id: face_id });
-                layout[i][j] = face_id;
+                faces.push(Face { map, offsets: (i, j), id: face_id });
+                layout[(i, j)] = face_id;
             }
         }
     }
-    Map { faces, layout }
+    if faces.is_empty() {
+        return Err(rows[0].error(rows[0].text, "a map with at least one '.' or '#'"));
+    }
+    Ok(Map { faces, layout })
 }
 
-fn parse_instruction(s: &str) -> Vec<Instruction> {
+fn parse_instruction(line: &Line) -> Result<Vec<Instruction>, ParseError> {
     let mut instructions: Vec<Instruction> = Vec::new();
     let mut number: usize = 0;
-    for ch in s.chars() {
+    for (column, ch) in line.chars() {
         match ch {
             'R' => {
                 instructions.push(Instruction::Forward(number)); 
@@ -246,52 +166,37 @@ fn parse_instruction(s: &str) -> Vec<Instruction> {
                 instructions.push(Instruction::Rotate(Rotation::CounterClockwise));
                 number = 0;
             },
-            d => number = number * 10 + d as usize - '0' as usize,
+            d => match d.to_digit(10) {
+                Some(digit) => number = number * 10 + digit as usize,
+                None => return Err(line.error_at(column, &d.to_string(), "a digit, 'L' or 'R'")),
+            },
         }
     }
     instructions.push(Instruction::Forward(number));
-    instructions
-}
-
-#[allow(dead_code)]
-fn print_map(map: &Map) -> () {
-    for face in &map.faces {
-        let m = face.map.len(); 
-        let n = face.map[0].len();
-        println!("{:?} {:?}", face.id, face.offsets);
-        for i in 0..m {
-            for j in 0..n {
-                match face.map[i][j] {
-                    Tile::Open => print!("."),
-                    Tile::Wall => print!("#"),
-                }
-            }
-            println!();
-        }
-    }
+    Ok(instructions)
 }
 
 fn part1(map: &Map, instructions: &Vec<Instruction>) -> usize {
     helper(map, instructions, teleport1(map))
 }
 
-fn teleport1(map: &Map) -> Teleport {
+pub fn teleport1(map: &Map) -> Teleport {
     let mut edges: Vec<(usize, Direction, usize, Direction)> = Vec::new();
     for face in &map.faces {
-        for dir in Direction::all_directions() {
+        for dir in Direction::ALL {
             let (mut i, mut j) = face.offsets;
             while {
-                (i, j) = dir.move_around((i, j), (map.layout.len(), map.layout[0].len()));
-                map.layout[i][j] == 0
+                (i, j) = move_around(dir, (i, j), (map.layout.rows(), map.layout.columns()));
+                map.layout[(i, j)] == 0
             } {}
-            edges.push((face.id, dir.clone(), map.layout[i][j], dir.opposite()))
+            edges.push((face.id, dir, map.layout[(i, j)], dir.opposite()))
         }
     }
-    Teleport { edges: edges, size: map.faces[0].map.len() }
+    Teleport { edges, size: map.faces[0].map.rows() }
 }
 
-fn part2(map: &Map, instructions: &Vec<Instruction>) -> usize {
-    helper(map, instructions, teleport2(map))
+fn part2(map: &Map, instructions: &Vec<Instruction>) -> Result<usize> {
+    Ok(helper(map, instructions, teleport2(map)?))
 }
 
 fn helper(map: &Map, instructions: &Vec<Instruction>, teleport: Teleport) -> usize {
@@ -301,27 +206,30 @@ fn helper(map: &Map, instructions: &Vec<Instruction>, teleport: Teleport) -> usi
             Instruction::Forward(steps) => {
                 for _ in 0..*steps {
                     let current_face = map.get_face_by_id(current_face_id);
-                    let next_move = current_dir.move_from((current_x, current_y), (current_face.map.len(), current_face.map[0].len()));
+                    let next_move = current_face.map.offset((current_x, current_y), current_dir.offset());
                     let (next_face_id, next_dir, next_x, next_y) = match next_move {
-                        Some((x, y)) => (current_face_id, current_dir.clone(), x, y),
+                        Some((x, y)) => (current_face_id, current_dir, x, y),
                         None => teleport.transport(current_face_id, &current_dir, current_x, current_y),
                     };
-                    if map.get_face_by_id(next_face_id).map[next_x][next_y] == Tile::Wall {
+                    if map.get_face_by_id(next_face_id).map[(next_x, next_y)] == Tile::Wall {
                         break;
                     }
                     (current_face_id, current_x, current_y, current_dir) = (next_face_id, next_x, next_y, next_dir);
                 };
             },
             Instruction::Rotate(rotation) => {
-                current_dir = rotation.rotate(&current_dir);
+                current_dir = current_dir.rotate(*rotation);
             }
         }
     }
     let final_face = map.get_face_by_id(current_face_id);
-    1000 * (current_x + 1 + final_face.offsets.0 * (final_face.map.len())) + 4 * (current_y + 1 + final_face.offsets.1 * (final_face.map[0].len())) + current_dir.score()
+    1000 * (current_x + 1 + final_face.offsets.0 * final_face.map.rows()) + 4 * (current_y + 1 + final_face.offsets.1 * final_face.map.columns()) + facing_score(current_dir)
 }
 
-fn teleport2(map: &Map) -> Teleport {
+pub fn teleport2(map: &Map) -> Result<Teleport> {
+    if map.faces.len() != 6 {
+        bail!("the map has {} faces, a cube has 6", map.faces.len());
+    }
     // standard cube:
     //  1
     // 234
@@ -335,80 +243,105 @@ fn teleport2(map: &Map) -> Teleport {
         (5, Direction::Left, 2, Direction::Down), (5, Direction::Right, 4, Direction::Down), (5, Direction::Up, 3, Direction::Down), (5, Direction::Down, 6, Direction::Up),
         (6, Direction::Left, 2, Direction::Left), (6, Direction::Right, 4, Direction::Right), (6, Direction::Up, 5, Direction::Down), (6, Direction::Down, 1, Direction::Up),
     ];
-    // map from the face id of the cube to the face id of the standard cube
+    // map from the face id of the cube to the face id of the standard cube, unfolding the net
+    // outwards from face 1 so that every face is placed next to one already on the cube
+    let neighbour_faces = |face_id: usize| Direction::ALL.into_iter()
+        .filter_map(|dir| map.layout.offset(map.get_face_by_id(face_id).offsets, dir.offset()).map(|next| (dir, map.layout[next])))
+        .filter(|(_, next_face_id)| *next_face_id != 0)
+        .collect::<Vec<(Direction, usize)>>();
+    let unfolding = bfs([1], |face_id| neighbour_faces(*face_id).into_iter().map(|(_, id)| id).collect::<Vec<usize>>(), |_| false);
     let mut map_to_stardard_cube: Vec<(usize, usize, Rotation)> = vec![(1, 1, Rotation::None)];
-    let mut start = 0;
-    let mut end = 1;
-    let mut visited = vec![false; 7];
-    while start < end {
-        for i in start..end {
-            let (current_face_id, standard_face_id, current_rotation) = map_to_stardard_cube[i];
-            visited[current_face_id] = true;
-            for current_dir in Direction::all_directions() {
-                if let Some((next_x, next_y)) = current_dir.move_from(map.get_face_by_id(current_face_id).offsets, (map.layout.len(), map.layout[0].len())) {
-                    if map.layout[next_x][next_y] != 0 && !visited[map.layout[next_x][next_y]] {
-                        let next_face_id = map.layout[next_x][next_y];
-                        let (next_standard_face_id, next_standard_face_dir) = standard
-                            .iter()
-                            .find(|(id, dir, _, _ )| *id == standard_face_id && *dir == current_rotation.rotate(&current_dir))
-                            .map(|(_, _, id, dir)| (id, dir))
-                            .unwrap();
-                        map_to_stardard_cube.push((next_face_id, *next_standard_face_id, Rotation::rotation(&current_dir.opposite(), next_standard_face_dir)));
-                    }
-                }
-            }
+    for next_face_id in &unfolding.settled()[1..] {
+        let current_face_id = *unfolding.predecessor(next_face_id).unwrap();
+        let current_dir = neighbour_faces(current_face_id).into_iter().find(|(_, id)| id == next_face_id).unwrap().0;
+        let (standard_face_id, current_rotation) = map_to_stardard_cube.iter().find(|(id, _, _)| *id == current_face_id).map(|(_, sid, rotation)| (*sid, *rotation)).unwrap();
+        let (next_standard_face_id, next_standard_face_dir) = standard
+            .iter()
+            .find(|(id, dir, _, _ )| *id == standard_face_id && *dir == current_dir.rotate(current_rotation))
+            .map(|(_, _, id, dir)| (id, dir))
+            .unwrap();
+        if let Some((other_face_id, _, _)) = map_to_stardard_cube.iter().find(|(_, sid, _)| sid == next_standard_face_id) {
+            bail!("faces {other_face_id} and {next_face_id} of the map fold onto the same side of the cube");
         }
-        start = end;
-        end = map_to_stardard_cube.len();
+        map_to_stardard_cube.push((*next_face_id, *next_standard_face_id, Rotation::between(current_dir.opposite(), *next_standard_face_dir)));
+    }
+
+    if map_to_stardard_cube.len() != map.faces.len() {
+        bail!("the faces of the map are not connected");
     }
 
     let mut edges: Vec<(usize, Direction, usize, Direction)> = Vec::new();
     for face in &map.faces {
-        for current_dir in Direction::all_directions() {
+        for current_dir in Direction::ALL {
             let (standard_face_id, forward_rotation) = map_to_stardard_cube.iter().find(|(id, _, _)| *id == face.id).map(|(_, sid, rotation)| (sid, rotation)).unwrap();
             let (next_stardard_face_id, next_standard_dir) = standard
                 .iter()
-                .find(|(id, dir, _, _)| *id == *standard_face_id && *dir == forward_rotation.rotate(&current_dir))
+                .find(|(id, dir, _, _)| *id == *standard_face_id && *dir == current_dir.rotate(*forward_rotation))
                 .map(|(_, _, id, dir)| (id, dir))
                 .unwrap();
             let (next_face_id, reverse_rotation) = map_to_stardard_cube.iter().find(|(_, sid, _)| *sid == *next_stardard_face_id).map(|(id, _, rotation)| (id, rotation)).unwrap();
-            edges.push((face.id, current_dir, *next_face_id, reverse_rotation.reverse().rotate(next_standard_dir)));
+            edges.push((face.id, current_dir, *next_face_id, next_standard_dir.rotate(reverse_rotation.reverse())));
         }
     }
-    Teleport { edges: edges, size: map.faces[0].map.len() }
+    Ok(Teleport { edges, size: map.faces[0].map.rows() })
 }
 
-// cargo test --bin day22 -- --nocapture
+const EXAMPLE: &str = r#"        ...#
+        .#..
+        #...
+        ....
+...#.......#
+........#...
+..#....#....
+..........#.
+        ...#....
+        .....#..
+        .#......
+        ......#.
+
+10R5L5R10L4R5L5"#;
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
     #[test]
     fn test1() {
-        let (map, path) = parse_input(&INPUT);
+        let (map, path) = parse_input(EXAMPLE).unwrap();
         let result = part1(&map, &path);
         assert_eq!(result, 6032);
     }
 
     #[test]
     fn test2() {
-        let (map, path) = parse_input(&INPUT);
-        let result = part2(&map, &path);
+        let (map, path) = parse_input(EXAMPLE).unwrap();
+        let result = part2(&map, &path).unwrap();
         assert_eq!(result, 5031);
     }
 
-    const INPUT: &str = r#"        ...#
-        .#..
-        #...
-        ....
-...#.......#
-........#...
-..#....#....
-..........#.
-        ...#....
-        .....#..
-        .#......
-        ......#.
+    #[test]
+    fn test_parse_errors() {
+        let map = |text: &str| parse_map(&crate::parse::lines(text)).unwrap_err();
+        let token = map("..\n.x");
+        assert_eq!((token.line, token.column), (2, 2));
+        // a face with a hole in it, and tiles off the corners of the faces
+        let holed = map("..  \n.   \n  ..\n  ..\n  ..\n  ..");
+        assert_eq!((holed.line, holed.column), (2, 2));
+        let stray = map(" .  \n    ");
+        assert_eq!((stray.line, stray.column), (1, 2));
+        let error = parse_instruction(&crate::parse::lines("10R5X")[0]).unwrap_err();
+        assert_eq!((error.column, error.token.as_str()), (5, "X"));
+    }
 
-10R5L5R10L4R5L5"#;
+    #[test]
+    fn test_not_a_cube() {
+        // six faces in a two by three block fold onto a corner of the cube three times
+        let (map, path) = parse_input("...\n...\n\n1R1").unwrap();
+        assert_eq!(part1(&map, &path), 2009);
+        assert!(part2(&map, &path).is_err());
+        let (map, path) = parse_input("...\n\n1").unwrap();
+        assert_eq!(part2(&map, &path).unwrap_err().to_string(), "the map has 3 faces, a cube has 6");
+        let (map, path) = parse_input("..  .\n ..\n  .\n\n1").unwrap();
+        assert_eq!(part2(&map, &path).unwrap_err().to_string(), "the faces of the map are not connected");
+    }
 }
\ No newline at end of file
@@ -1,17 +1,12 @@
-use anyhow::Result;
-use std::fs;
-use std::collections::HashSet;
+use anyhow::{bail, Result};
+use crate::Solution;
+use crate::geometry::Direction;
+use crate::grid::{Grid, Position};
+use crate::search::bfs;
+use num::integer::lcm;
 
 #[derive(Debug, PartialEq, Clone)]
-enum Direction {
-    Left,
-    Right,
-    Up,
-    Down,
-}
-
-#[derive(Debug, PartialEq, Clone)]
-enum Ground {
+pub enum Ground {
     Blizzard(Vec<Direction>),
     Wall,
 }
@@ -32,149 +27,178 @@ impl Ground {
     }
 }
 
-fn main() -> Result<()> {
-    let map: Vec<Vec<Ground>> = parse_input(fs::read_to_string("day24.input")?.as_str());
-    println!("{}", part1(&map));
-    println!("{}", part2(&map));
-    Ok(())
-}
+pub struct Day24;
+
+impl Solution for Day24 {
+    const DAY: u8 = 24;
+    const EXAMPLE: Option<&'static str> = Some(EXAMPLE);
+    type Input = Grid<Ground>;
+    type Part1 = usize;
+    type Part2 = usize;
+
+    fn parse(input: &str) -> Result<Self::Input> {
+        parse_input(input)
+    }
+
+    fn part1(map: &Self::Input) -> Result<Self::Part1> {
+        part1(map)
+    }
 
-fn parse_input(input: &str) -> Vec<Vec<Ground>> {
-    input.split("\n")
-        .map(|s| s.chars()
-            .map(|c| match c {
-                '#' => Ground::Wall,
-                '.' => Ground::Blizzard(vec![]),
-                '>' => Ground::Blizzard(vec![Direction::Right]),
-                '<' => Ground::Blizzard(vec![Direction::Left]),
-                '^' => Ground::Blizzard(vec![Direction::Up]),
-                'v' => Ground::Blizzard(vec![Direction::Down]),
-                _ => panic!(),
-            })
-            .collect())
-        .collect()
+    fn part2(map: &Self::Input) -> Result<Self::Part2> {
+        part2(map)
+    }
 }
 
-#[warn(dead_code)]
-fn print_map(map: &Vec<Vec<Ground>>) -> () {
-    for i in 0..map.len() {
-        for j in 0..map[i].len() {
-            print!("{}", match &map[i][j] {
-                Ground::Wall => '#',
-                Ground::Blizzard(dirs) => {
-                    if dirs.len() == 0 {
-                        '.'
-                    } else if dirs.len() == 1 {
-                        match dirs[0] {
-                            Direction::Left => '<',
-                            Direction::Right => '>',
-                            Direction::Up => '^',
-                            Direction::Down => 'v',
-                        }
-                    } else {
-                        char::from_digit(dirs.len() as u32, 10).unwrap() 
-                    }
-                },
-            });
-        }
-        println!();
+fn parse_input(input: &str) -> Result<Grid<Ground>> {
+    let map = Grid::parse(input, |c| match c {
+        '#' => Some(Ground::Wall),
+        '.' => Some(Ground::Blizzard(vec![])),
+        '>' => Some(Ground::Blizzard(vec![Direction::Right])),
+        '<' => Some(Ground::Blizzard(vec![Direction::Left])),
+        '^' => Some(Ground::Blizzard(vec![Direction::Up])),
+        'v' => Some(Ground::Blizzard(vec![Direction::Down])),
+        _ => None,
+    }, "one of #, ., >, <, ^, v")?;
+    if map.rows() < 3 || map.columns() < 3 {
+        bail!("a valley of {} by {} has no room inside its walls", map.rows(), map.columns());
     }
+    Ok(map)
 }
 
-fn next_map(map: &Vec<Vec<Ground>>) -> Vec<Vec<Ground>> {
-    let m = map.len();
-    let n = map[0].len();
-    let mut new_map = vec![vec![Ground::Wall; n]; m];
-    new_map[0][1] = Ground::Blizzard(vec![]);
-    new_map[m - 1][n - 2] = Ground::Blizzard(vec![]);
+fn next_map(map: &Grid<Ground>) -> Grid<Ground> {
+    let m = map.rows();
+    let n = map.columns();
+    let mut new_map = Grid::new(m, n, Ground::Wall);
+    new_map[(0, 1)] = Ground::Blizzard(vec![]);
+    new_map[(m - 1, n - 2)] = Ground::Blizzard(vec![]);
     for i in 1..(m - 1) {
         for j in 1..(n - 1) {
             let mut dirs: Vec<Direction> = Vec::new();
-            if map[i - 1][j].contains(&Direction::Down) || (map[i - 1][j] == Ground::Wall && map[m - 2][j].contains(&Direction::Down)) {
+            if map[(i - 1, j)].contains(&Direction::Down) || (map[(i - 1, j)] == Ground::Wall && map[(m - 2, j)].contains(&Direction::Down)) {
                 dirs.push(Direction::Down);
             }
-            if map[i + 1][j].contains(&Direction::Up) || (map[i + 1][j] == Ground::Wall && map[1][j].contains(&Direction::Up)) {
+            if map[(i + 1, j)].contains(&Direction::Up) || (map[(i + 1, j)] == Ground::Wall && map[(1, j)].contains(&Direction::Up)) {
                 dirs.push(Direction::Up);
             }
-            if map[i][j - 1].contains(&Direction::Right) || (map[i][j - 1] == Ground::Wall && map[i][n - 2].contains(&Direction::Right)) {
+            if map[(i, j - 1)].contains(&Direction::Right) || (map[(i, j - 1)] == Ground::Wall && map[(i, n - 2)].contains(&Direction::Right)) {
                 dirs.push(Direction::Right);
             }
-            if map[i][j + 1].contains(&Direction::Left) || (map[i][j + 1] == Ground::Wall && map[i][1].contains(&Direction::Left)) {
+            if map[(i, j + 1)].contains(&Direction::Left) || (map[(i, j + 1)] == Ground::Wall && map[(i, 1)].contains(&Direction::Left)) {
                 dirs.push(Direction::Left);
             }
-            new_map[i][j] = Ground::Blizzard(dirs);
+            new_map[(i, j)] = Ground::Blizzard(dirs);
         }
     }
     new_map
 }
 
-fn bfs(map: &Vec<Vec<Ground>>, start: (usize, usize), end: (usize, usize)) -> (usize, Vec<Vec<Ground>>) {
-    let m = map.len();
-    let n = map[0].len();  
-    let mut next_round = HashSet::from([start.clone()]);
-    let mut minute = 0;
-    let mut current_map = map.clone();
-    loop {
-        minute += 1;
-        let current_round = next_round.clone();
-        current_map = next_map(&current_map);
-        next_round = HashSet::new();
-        for (i, j) in current_round {
-            if current_map[i][j].is_clear() {
-                next_round.insert((i, j));
-            }
-            if i > 0 && current_map[i - 1][j].is_clear() {
-                next_round.insert((i - 1, j));
-            }
-            if i + 1 < m && current_map[i + 1][j].is_clear() {
-                next_round.insert((i + 1, j));
-            }
-            if j > 0 && current_map[i][j - 1].is_clear() {
-                next_round.insert((i, j - 1));
-            }
-            if j + 1 < n && current_map[i][j + 1].is_clear() {
-                next_round.insert((i, j + 1));
-            }
-        }
-        if next_round.iter().any(|(i, j)| (*i, *j) == end) {
-            break;
-        }
+/// Which cells are clear at each minute of the blizzards' cycle; they come back to where they
+/// started every `lcm(rows, columns)` minutes of the inner valley.
+fn clear_maps(map: &Grid<Ground>) -> Vec<Grid<bool>> {
+    let period = lcm(map.rows() - 2, map.columns() - 2);
+    let mut clear = Vec::with_capacity(period);
+    let mut current = map.clone();
+    for _ in 0..period {
+        clear.push(current.map(Ground::is_clear));
+        current = next_map(&current);
+    }
+    clear
+}
+
+/// The minute at which the expedition first reaches `end` when leaving `start` at `minute`,
+/// searching over positions at each minute of the blizzards' cycle.
+fn trip(clear: &[Grid<bool>], start: Position, end: Position, minute: usize) -> Result<usize> {
+    let period = clear.len();
+    let search = bfs([(start, minute % period)],
+        |&(position, time)| {
+            let time = (time + 1) % period;
+            let map = &clear[time];
+            std::iter::once(position).chain(map.neighbours4(position))
+                .filter(|step| map[*step])
+                .map(|step| (step, time))
+                .collect::<Vec<(Position, usize)>>()
+        },
+        |(position, _)| *position == end);
+    match search.goal_cost() {
+        Some(minutes) => Ok(minute + minutes),
+        None => bail!("the blizzards block every path from {start:?} to {end:?}"),
     }
-    (minute, current_map)
 }
 
-fn part1(map: &Vec<Vec<Ground>>) -> usize {
-    bfs(map, (0, 1), (map.len() - 1, map[0].len() - 2)).0
+fn part1(map: &Grid<Ground>) -> Result<usize> {
+    let (start, end) = ((0, 1), (map.rows() - 1, map.columns() - 2));
+    trip(&clear_maps(map), start, end, 0)
 }
 
-fn part2(map: &Vec<Vec<Ground>>) -> usize {
-    let (time1, map1) = bfs(map, (0, 1), (map.len() - 1, map[0].len() - 2));
-    let (time2, map2) = bfs(&map1, (map1.len() - 1, map1[0].len() - 2), (0, 1));
-    let (time3, _) = bfs(&map2, (0, 1), (map2.len() - 1, map2[0].len() - 2));
-    time1 + time2 + time3
+fn part2(map: &Grid<Ground>) -> Result<usize> {
+    let (start, end) = ((0, 1), (map.rows() - 1, map.columns() - 2));
+    let clear = clear_maps(map);
+    let there = trip(&clear, start, end, 0)?;
+    let back = trip(&clear, end, start, there)?;
+    trip(&clear, start, end, back)
 }
 
-// cargo test --bin day24 -- --nocapture
+const EXAMPLE: &str = "#.######\n\
+                       #>>.<^<#\n\
+                       #.<..<<#\n\
+                       #>v.><>#\n\
+                       #<^v^^>#\n\
+                       ######.#";
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    /// Draws the valley as the puzzle does, with a digit where several blizzards meet.
+    fn render(map: &Grid<Ground>) -> String {
+        map.render(|ground| match ground {
+            Ground::Wall => '#',
+            Ground::Blizzard(dirs) => {
+                if dirs.is_empty() {
+                    '.'
+                } else if dirs.len() == 1 {
+                    match dirs[0] {
+                        Direction::Left => '<',
+                        Direction::Right => '>',
+                        Direction::Up => '^',
+                        Direction::Down => 'v',
+                    }
+                } else {
+                    char::from_digit(dirs.len() as u32, 10).unwrap()
+                }
+            },
+        })
+    }
+
     #[test]
     fn test1() {
-        let result = part1(&parse_input(&INPUT));
+        let result = part1(&parse_input(EXAMPLE).unwrap()).unwrap();
         assert_eq!(result, 18);
     }
 
     #[test]
     fn test2() {
-        let result = part2(&parse_input(&INPUT));
+        let result = part2(&parse_input(EXAMPLE).unwrap()).unwrap();
         assert_eq!(result, 54);
     }
 
-    const INPUT: &str ="#.######\n\
-                        #>>.<^<#\n\
-                        #.<..<<#\n\
-                        #>v.><>#\n\
-                        #<^v^^>#\n\
-                        ######.#";                
+    #[test]
+    fn test_valley() {
+        let map = parse_input(EXAMPLE).unwrap();
+        assert_eq!(render(&map), format!("{EXAMPLE}\n"));
+        assert_eq!(render(&next_map(&map)), "#.######\n\
+                                          #.>3.<.#\n\
+                                          #<..<<.#\n\
+                                          #>2.22.#\n\
+                                          #>v..^<#\n\
+                                          ######.#\n");
+    }
+
+    #[test]
+    fn test_blocked() {
+        // the exit is walled in, as in an input cut short
+        let map = parse_input("#.######\n#>>.<^<#\n#.<..<<#\n########").unwrap();
+        assert!(part1(&map).is_err());
+        assert!(parse_input("#.#\n#.#").is_err());
+    }
 }
\ No newline at end of file
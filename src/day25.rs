@@ -1,17 +1,42 @@
-use anyhow::Result;
-use std::fs;
+use anyhow::{bail, Result};
+use crate::Solution;
+use crate::parse::lines;
 
-fn main() -> Result<()> {
-    let snafu: Vec<String> = parse_input(fs::read_to_string("day25.input")?.as_str());
-    println!("{}", part1(&snafu));
-    Ok(())
+pub struct Day25;
+
+impl Solution for Day25 {
+    const DAY: u8 = 25;
+    const PARTS: u8 = 1;
+    const EXAMPLE: Option<&'static str> = Some(EXAMPLE);
+    type Input = Vec<String>;
+    type Part1 = String;
+    type Part2 = String;
+
+    fn parse(input: &str) -> Result<Self::Input> {
+        parse_input(input)
+    }
+
+    fn part1(snafu: &Self::Input) -> Result<Self::Part1> {
+        Ok(part1(snafu))
+    }
+
+    fn part2(_: &Self::Input) -> Result<Self::Part2> {
+        bail!("day 25 has no second puzzle")
+    }
 }
 
-fn parse_input(input: &str) -> Vec<String> {
-    input.split("\n").map(|s| s.to_string()).collect()
+fn parse_input(input: &str) -> Result<Vec<String>> {
+    let mut numbers: Vec<String> = Vec::new();
+    for line in lines(input) {
+        if let Some((column, c)) = line.chars().find(|(_, c)| !"210-=".contains(*c)) {
+            bail!(line.error_at(column, &c.to_string(), "a SNAFU digit"));
+        }
+        numbers.push(line.text.to_string());
+    }
+    Ok(numbers)
 }
 
-fn snafu_to_decimal(snafu: &str) -> i64 {
+pub fn snafu_to_decimal(snafu: &str) -> i64 {
     let mut decimal = 0;
     for c in snafu.chars() {
         decimal = decimal * 5 + match c {
@@ -26,7 +51,7 @@ fn snafu_to_decimal(snafu: &str) -> i64 {
     decimal
 }
 
-fn decimal_to_snafu(decimal: i64) -> String {
+pub fn decimal_to_snafu(decimal: i64) -> String {
     let mut snafu: Vec<char> = Vec::new();
     let mut number = decimal;
     while number != 0 {
@@ -43,32 +68,31 @@ fn decimal_to_snafu(decimal: i64) -> String {
     snafu.iter().rev().collect()
 }
 
-fn part1(snafu: &Vec<String>) -> String {
+fn part1(snafu: &[String]) -> String {
     decimal_to_snafu(snafu.iter().map(|s| snafu_to_decimal(s)).sum())
 }
 
-// cargo test --bin day25 -- --nocapture
+const EXAMPLE: &str = "1=-0-2\n\
+                       12111\n\
+                       2=0=\n\
+                       21\n\
+                       2=01\n\
+                       111\n\
+                       20012\n\
+                       112\n\
+                       1=-1=\n\
+                       1-12\n\
+                       12\n\
+                       1=\n\
+                       122";
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
     #[test]
     fn test1() {
-        let result = part1(&parse_input(&INPUT));
+        let result = part1(&parse_input(EXAMPLE).unwrap());
         assert_eq!(result, "2=-1=0");
     }
-
-    const INPUT: &str ="1=-0-2\n\
-                        12111\n\
-                        2=0=\n\
-                        21\n\
-                        2=01\n\
-                        111\n\
-                        20012\n\
-                        112\n\
-                        1=-1=\n\
-                        1-12\n\
-                        12\n\
-                        1=\n\
-                        122";                
 }
\ No newline at end of file
@@ -1,33 +1,67 @@
-use anyhow::Result;
-use std::fs;
+use anyhow::{anyhow, bail, Result};
+use crate::parse::lines;
+use crate::Solution;
 use std::collections::HashMap;
 
-fn main() -> Result<()> {
-    let part1: usize = fs::read_to_string("day3.input")?
-        .split("\n")
+pub struct Day3;
+
+impl Solution for Day3 {
+    const DAY: u8 = 3;
+    const EXAMPLE: Option<&'static str> = Some(EXAMPLE);
+    type Input = Vec<String>;
+    type Part1 = usize;
+    type Part2 = usize;
+
+    fn parse(input: &str) -> Result<Self::Input> {
+        parse_input(input)
+    }
+
+    fn part1(rucksacks: &Self::Input) -> Result<Self::Part1> {
+        part1(rucksacks)
+    }
+
+    fn part2(rucksacks: &Self::Input) -> Result<Self::Part2> {
+        part2(rucksacks)
+    }
+}
+
+fn parse_input(input: &str) -> Result<Vec<String>> {
+    let mut rucksacks = Vec::new();
+    for line in lines(input) {
+        if let Some((column, c)) = line.chars().find(|(_, c)| !c.is_ascii_alphabetic()) {
+            return Err(line.error_at(column, &c.to_string(), "an item letter").into());
+        }
+        if line.text.len() % 2 != 0 {
+            return Err(line.error(line.text, "an even number of items, half in each compartment").into());
+        }
+        rucksacks.push(line.text.to_string());
+    }
+    Ok(rucksacks)
+}
+
+fn part1(rucksacks: &[String]) -> Result<usize> {
+    rucksacks.iter()
         .map(|line| line.split_at(line.len()/2))
-        .map(|(first, second)| find_common(first, second))
-        .map(|common| priority(&common))
-        .sum();
-    println!("{part1}");
-
-    let part2: usize = fs::read_to_string("day3.input")?
-        .split("\n")
-        .collect::<Vec<&str>>()
-        .chunks(3)
-        .map(|chunks| find_common3(chunks[0], chunks[1], chunks[2]))
-        .map(|common| priority(&common))
-        .sum();
-    println!("{part2}");
-    Ok(())
+        .map(|(first, second)| find_common(&[first, second]))
+        .map(|common| common.map(|common| priority(&common)))
+        .sum()
 }
 
-fn find_common(first: &str, second: &str) -> char {
-    first.chars().find(|c| second.contains(*c)).expect("common item between {first} and {second}")
+fn part2(rucksacks: &[String]) -> Result<usize> {
+    let groups = rucksacks.chunks_exact(3);
+    if !groups.remainder().is_empty() {
+        bail!("{} rucksacks do not split into groups of three", rucksacks.len());
+    }
+    groups
+        .map(|chunks| find_common(&[&chunks[0], &chunks[1], &chunks[2]]))
+        .map(|common| common.map(|common| priority(&common)))
+        .sum()
 }
 
-fn find_common3(first: &str, second: &str, third: &str) -> char {
-    first.chars().find(|c| second.contains(*c) && third.contains(*c)).expect("common item between {first}, {second}, and {third}")
+fn find_common(items: &[&str]) -> Result<char> {
+    items[0].chars()
+        .find(|c| items[1..].iter().all(|other| other.contains(*c)))
+        .ok_or_else(|| anyhow!("no item in common between {}", items.join(", ")))
 }
 
 fn priority(item: &char) -> usize {
@@ -36,4 +70,39 @@ fn priority(item: &char) -> usize {
         .map(|(index, character)| (character, index+1))
         .collect();
     *priority_map.get(item).expect("item {item}")
-}
\ No newline at end of file
+}
+
+const EXAMPLE: &str = "vJrwpWtwJgWrhcsFMMfFFhFp\n\
+                       jqHRNqRjqzjGDLGLrsFMfFZSrLrFZsSL\n\
+                       PmmdzqPrVvPwwTWBwg\n\
+                       wMqvLMZHhHMvwLHjbvcjnnSBnvTQFn\n\
+                       ttgJtRGJQctTZtZT\n\
+                       CrZsJsPPZsGzwwsLwLmpwMDw";
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test1() {
+        let result = part1(&parse_input(EXAMPLE).unwrap()).unwrap();
+        assert_eq!(result, 157);
+    }
+
+    #[test]
+    fn test2() {
+        let result = part2(&parse_input(EXAMPLE).unwrap()).unwrap();
+        assert_eq!(result, 70);
+    }
+
+    #[test]
+    fn test_errors() {
+        let error = parse_input("abc\nab\nab").unwrap_err().downcast::<crate::parse::ParseError>().unwrap();
+        assert_eq!((error.line, error.column), (1, 1));
+        let ungrouped = parse_input("aa\nbb\ncc\ndd").unwrap();
+        assert_eq!(part1(&ungrouped).unwrap(), 10);
+        assert!(part2(&ungrouped).is_err());
+        assert!(part1(&parse_input("ab\naa\naa").unwrap()).is_err());
+        assert!(part2(&parse_input("aa\nbb\nab").unwrap()).is_err());
+    }
+}
@@ -1,8 +1,9 @@
 use anyhow::Result;
-use std::fs;
+use crate::parse::{lines, Line, ParseError};
+use crate::Solution;
 
 #[derive(Debug)]
-struct Assignment {
+pub struct Assignment {
     start: u32,
     end: u32,
 }
@@ -21,51 +22,92 @@ impl Assignment {
     }
 
     fn of(start: u32, end: u32) -> Assignment {
-        Assignment { start: start, end: end }
+        Assignment { start, end }
     }
 
-    fn parse(pair_string: &str) -> Assignment {
-        pair_string.split_once("-")
-          .map(|(start, end)| (start.parse::<u32>().expect("integer {start}"), end.parse::<u32>().expect("integer {end}")))
-          .map(|(start, end)| Assignment::of(start, end))
-          .expect("split {pair_string} with '-'")
+    fn parse(line: &Line, pair_string: &str) -> Result<Assignment, ParseError> {
+        let (start, end) = pair_string.split_once('-').ok_or_else(|| line.error(pair_string, "a range like 2-4"))?;
+        Ok(Assignment::of(line.parse::<u32>(start, "a section id")?, line.parse::<u32>(end, "a section id")?))
     }
 }
 
-fn main() -> Result<()> {
-    let assignments: Vec<(Assignment, Assignment)> = fs::read_to_string("day4.input")?
-        .split("\n")
-        .map(|line| line.split_once(",").expect("split {line} with ','"))
-        .map(|(first, second)| (Assignment::parse(first), Assignment::parse(second)))
-        .collect();
-    let part1: usize = assignments.iter()
+pub struct Day4;
+
+impl Solution for Day4 {
+    const DAY: u8 = 4;
+    const EXAMPLE: Option<&'static str> = Some(EXAMPLE);
+    type Input = Vec<(Assignment, Assignment)>;
+    type Part1 = usize;
+    type Part2 = usize;
+
+    fn parse(input: &str) -> Result<Self::Input> {
+        parse_input(input)
+    }
+
+    fn part1(assignments: &Self::Input) -> Result<Self::Part1> {
+        Ok(part1(assignments))
+    }
+
+    fn part2(assignments: &Self::Input) -> Result<Self::Part2> {
+        Ok(part2(assignments))
+    }
+}
+
+fn parse_input(input: &str) -> Result<Vec<(Assignment, Assignment)>> {
+    let mut assignments = Vec::new();
+    for line in lines(input) {
+        let (first, second) = line.split_once(",")?;
+        assignments.push((Assignment::parse(&line, first)?, Assignment::parse(&line, second)?));
+    }
+    Ok(assignments)
+}
+
+fn part1(assignments: &[(Assignment, Assignment)]) -> usize {
+    assignments.iter()
       .filter(|(first, second)| first.contains(second) || second.contains(first))
-      .count();
-    println!("{part1}");
-    
-    let part2: usize = assignments.iter()
+      .count()
+}
+
+fn part2(assignments: &[(Assignment, Assignment)]) -> usize {
+    assignments.iter()
       .filter(|(first, second)| first.overlaps(second))
-      .count();
-    println!("{part2}");
-    Ok(())
+      .count()
 }
 
+const EXAMPLE: &str = "2-4,6-8\n\
+                       2-3,4-5\n\
+                       5-7,7-9\n\
+                       2-8,3-7\n\
+                       6-6,4-6\n\
+                       2-6,4-8";
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test1() {
+        let result = part1(&parse_input(EXAMPLE).unwrap());
+        assert_eq!(result, 2);
+    }
+
+    #[test]
+    fn test2() {
+        let result = part2(&parse_input(EXAMPLE).unwrap());
+        assert_eq!(result, 4);
+    }
+
     #[test]
     fn test_overlap() {
-        assert_eq!(Assignment::of(1, 1).overlaps(&Assignment::of(1, 1)), true);
-        assert_eq!(Assignment::of(1, 2).overlaps(&Assignment::of(2, 2)), true);
-        assert_eq!(Assignment::of(2, 2).overlaps(&Assignment::of(1, 2)), true);
-        assert_eq!(Assignment::of(1, 4).overlaps(&Assignment::of(2, 3)), true);
-        assert_eq!(Assignment::of(2, 3).overlaps(&Assignment::of(1, 4)), true);
-
-        assert_eq!(Assignment::of(1, 1).overlaps(&Assignment::of(2, 2)), false);
-        assert_eq!(Assignment::of(2, 2).overlaps(&Assignment::of(1, 1)), false);
-        assert_eq!(Assignment::of(1, 2).overlaps(&Assignment::of(3, 4)), false);
-        assert_eq!(Assignment::of(3, 4).overlaps(&Assignment::of(1, 2)), false);
+        assert!(Assignment::of(1, 1).overlaps(&Assignment::of(1, 1)));
+        assert!(Assignment::of(1, 2).overlaps(&Assignment::of(2, 2)));
+        assert!(Assignment::of(2, 2).overlaps(&Assignment::of(1, 2)));
+        assert!(Assignment::of(1, 4).overlaps(&Assignment::of(2, 3)));
+        assert!(Assignment::of(2, 3).overlaps(&Assignment::of(1, 4)));
+
+        assert!(!Assignment::of(1, 1).overlaps(&Assignment::of(2, 2)));
+        assert!(!Assignment::of(2, 2).overlaps(&Assignment::of(1, 1)));
+        assert!(!Assignment::of(1, 2).overlaps(&Assignment::of(3, 4)));
+        assert!(!Assignment::of(3, 4).overlaps(&Assignment::of(1, 2)));
     }
-}
\ No newline at end of file
+}
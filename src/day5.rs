@@ -1,85 +1,119 @@
-use anyhow::Result;
-use std::fs;
+use anyhow::{anyhow, bail, Result};
+use crate::parse::{blocks, Line, ParseError};
+use crate::Solution;
 use std::collections::LinkedList;
 use regex::Regex;
 
-#[derive(Debug)]
-struct Stack {
+#[derive(Debug, Clone)]
+pub struct Stack {
     stacks: Vec<LinkedList<char>>
 }
 
 impl Stack {
-    fn from_drawing(drawing: &str) -> Stack {
-        let matrics: Vec<Vec<char>> = drawing.split("\n")
-            .map(|line| line.chars().collect::<Vec<char>>().chunks(4).map(|c| c[1]).collect::<Vec<char>>())
-            .collect::<Vec<Vec<char>>>()
-            .into_iter()
-            .rev()
-            .skip(1)
-            .collect();
-        let n = matrics[0].len();
+    fn from_drawing(drawing: &[Line]) -> Result<Stack, ParseError> {
+        // the last row numbers the stacks, the crate letters sit in every fourth column above it
+        let (labels, rows) = drawing.split_last().expect("blocks are never empty");
+        let n = labels.text.split_whitespace().count();
         let mut stacks: Vec<LinkedList<char>> = vec![LinkedList::new(); n];
-        for row in &matrics {
-            for i in 0..n {
-                if row[i] != ' ' {
-                    stacks[i].push_back(row[i]);
+        for row in rows.iter().rev() {
+            let chars: Vec<char> = row.text.chars().collect();
+            for (i, stack) in stacks.iter_mut().enumerate() {
+                match chars.get(4 * i + 1) {
+                    Some(c) if c.is_ascii_uppercase() => stack.push_back(*c),
+                    Some(' ') | None => (),
+                    Some(c) => return Err(row.error_at(4 * i + 2, &c.to_string(), "a crate letter")),
                 }
             }
         }
-        Stack {stacks: stacks}   
+        Ok(Stack {stacks})
     }
 }
 
 #[derive(Debug)]
-struct Move {
+pub struct Move {
+    line: usize,
     number: u32,
     from: usize,
     to: usize,
 }
 
 impl Move {
-    fn parse(instruction: &str) -> Move {
-        let re = Regex::new(r"move (?P<number>\d+) from (?P<from>\d+) to (?P<to>\d+)").expect("invalid regex");
-        let caps = re.captures(instruction).expect("parse intruction {instruction}");
-        Move {
-            number: caps["number"].parse::<u32>().unwrap(),
-            from: caps["from"].parse::<usize>().unwrap(),
-            to: caps["to"].parse::<usize>().unwrap(),
-        }
+    fn parse(instruction: &Line, stacks: usize) -> Result<Move, ParseError> {
+        let re = Regex::new(r"^move (?P<number>\d+) from (?P<from>\d+) to (?P<to>\d+)$").expect("invalid regex");
+        let caps = re.captures(instruction.text).ok_or_else(|| instruction.error(instruction.text, "move N from A to B"))?;
+        let stack = |name: &str| {
+            let token = caps.name(name).unwrap().as_str();
+            match instruction.parse::<usize>(token, "a stack number")? {
+                index if (1..=stacks).contains(&index) => Ok(index),
+                _ => Err(instruction.error(token, &format!("a stack between 1 and {stacks}"))),
+            }
+        };
+        Ok(Move {
+            line: instruction.number,
+            number: instruction.parse::<u32>(&caps["number"], "a number of crates")?,
+            from: stack("from")?,
+            to: stack("to")?,
+        })
     }
 
-    fn parse_multiple(instructions: &str) -> Vec<Move> {
-        instructions.split("\n")
-          .map(|instruction| Move::parse(instruction))
+    fn parse_multiple(instructions: &[Line], stacks: usize) -> Result<Vec<Move>, ParseError> {
+        instructions.iter()
+          .map(|instruction| Move::parse(instruction, stacks))
           .collect()
     }
 }
 
-fn main() -> Result<()> {
-    let (mut stack, moves): (Stack, Vec<Move>) = fs::read_to_string("day5.input")?
-      .split_once("\n\n")
-      .map(|(drawing, moves)| (Stack::from_drawing(drawing), Move::parse_multiple(moves)))
-      .expect("invalid input");
+pub struct Day5;
+
+impl Solution for Day5 {
+    const DAY: u8 = 5;
+    const EXAMPLE: Option<&'static str> = Some(EXAMPLE);
+    type Input = (Stack, Vec<Move>);
+    type Part1 = String;
+    type Part2 = String;
+
+    fn parse(input: &str) -> Result<Self::Input> {
+        parse_input(input)
+    }
+
+    fn part1(input: &Self::Input) -> Result<Self::Part1> {
+        let (stack, moves) = input;
+        part1(stack.clone(), moves)
+    }
 
+    fn part2(input: &Self::Input) -> Result<Self::Part2> {
+        let (stack, moves) = input;
+        part2(stack.clone(), moves)
+    }
+}
+
+fn parse_input(input: &str) -> Result<(Stack, Vec<Move>)> {
+    match blocks(input).as_slice() {
+        [drawing, moves] => {
+            let stack = Stack::from_drawing(drawing)?;
+            let moves = Move::parse_multiple(moves, stack.stacks.len())?;
+            Ok((stack, moves))
+        },
+        _ => Err(anyhow!("expected the drawing and the moves separated by a blank line")),
+    }
+}
+
+fn part1(mut stack: Stack, moves: &[Move]) -> Result<String> {
     for m in moves {
         for _ in 0..m.number {
-            let element: char = stack.stacks[m.from - 1].pop_back().expect("stack has elements");
+            let element: char = pop(&mut stack, m)?;
             stack.stacks[m.to - 1].push_back(element);
         }
     }
 
-    let part1: String = stack.stacks.iter().flat_map(|list| list.back()).collect::<String>();
-    println!("{part1}");
-
-    let (mut stack, moves): (Stack, Vec<Move>) = fs::read_to_string("day5.input")?
-      .split_once("\n\n")
-      .map(|(drawing, moves)| (Stack::from_drawing(drawing), Move::parse_multiple(moves)))
-      .expect("input has two parts separating by two newlines");
+    Ok(stack.stacks.iter().flat_map(|list| list.back()).collect::<String>())
+}
 
+fn part2(mut stack: Stack, moves: &[Move]) -> Result<String> {
     for m in moves {
         let mut temp_stack = LinkedList::new();
         for _ in 0..m.number {
-            let element: char = stack.stacks[m.from - 1].pop_back().unwrap();
+            let element: char = pop(&mut stack, m)?;
             temp_stack.push_front(element);
         }
         for element in temp_stack {
@@ -87,7 +121,49 @@ fn main() -> Result<()> {
         }
     }
 
-    let part2: String = stack.stacks.iter().filter_map(|list| list.back()).collect::<String>();
-    println!("{part2}");
-    Ok(())
-}
\ No newline at end of file
+    Ok(stack.stacks.iter().filter_map(|list| list.back()).collect::<String>())
+}
+
+fn pop(stack: &mut Stack, m: &Move) -> Result<char> {
+    match stack.stacks[m.from - 1].pop_back() {
+        Some(element) => Ok(element),
+        None => bail!("line {}: cannot move {} crates, stack {} runs out", m.line, m.number, m.from),
+    }
+}
+
+const EXAMPLE: &str = r#"    [D]    
+[N] [C]    
+[Z] [M] [P]
+ 1   2   3 
+
+move 1 from 2 to 1
+move 3 from 1 to 3
+move 2 from 2 to 1
+move 1 from 1 to 2"#;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test1() {
+        let (stack, moves) = parse_input(EXAMPLE).unwrap();
+        let result = part1(stack, &moves).unwrap();
+        assert_eq!(result, "CMZ");
+    }
+
+    #[test]
+    fn test2() {
+        let (stack, moves) = parse_input(EXAMPLE).unwrap();
+        let result = part2(stack, &moves).unwrap();
+        assert_eq!(result, "MCD");
+    }
+
+    #[test]
+    fn test_empty_stack() {
+        let (stack, moves) = parse_input("[A]\n 1   2\n\nmove 5 from 1 to 2").unwrap();
+        let error = part1(stack.clone(), &moves).unwrap_err();
+        assert_eq!(error.to_string(), "line 4: cannot move 5 crates, stack 1 runs out");
+        assert!(part2(stack, &moves).is_err());
+    }
+}
@@ -1,27 +1,87 @@
-use anyhow::Result;
-use std::fs;
+use anyhow::{anyhow, bail, Result};
+use crate::Solution;
+use crate::parse::lines;
 use std::collections::HashSet;
 
-fn main() -> Result<()> {
-    let datastream: Vec<char> = fs::read_to_string("day6.input")?
-        .chars()
-        .collect();
+pub struct Day6;
 
-    let part1 = datastream
-        .windows(4)
-        .enumerate()
-        .find(|(_, w)| w.iter().collect::<HashSet<&char>>().len() == w.len())
-        .map(|(i, _)| i + 4)
-        .expect("index");
-    println!("{part1}");
+impl Solution for Day6 {
+    const DAY: u8 = 6;
+    const EXAMPLE: Option<&'static str> = Some(EXAMPLE);
+    type Input = Vec<char>;
+    type Part1 = usize;
+    type Part2 = usize;
+
+    fn parse(input: &str) -> Result<Self::Input> {
+        parse_input(input)
+    }
+
+    fn part1(datastream: &Self::Input) -> Result<Self::Part1> {
+        part1(datastream)
+    }
+
+    fn part2(datastream: &Self::Input) -> Result<Self::Part2> {
+        part2(datastream)
+    }
+}
+
+fn parse_input(input: &str) -> Result<Vec<char>> {
+    let mut datastream = Vec::new();
+    for line in lines(input) {
+        for (column, c) in line.chars() {
+            if !c.is_ascii_lowercase() {
+                bail!(line.error_at(column, &c.to_string(), "a lowercase letter"));
+            }
+            datastream.push(c);
+        }
+    }
+    if datastream.is_empty() {
+        bail!("empty datastream");
+    }
+    Ok(datastream)
+}
 
-    let part2 = datastream
-        .windows(14)
+fn part1(datastream: &[char]) -> Result<usize> {
+    find_marker(datastream, 4)
+}
+
+fn part2(datastream: &[char]) -> Result<usize> {
+    find_marker(datastream, 14)
+}
+
+fn find_marker(datastream: &[char], size: usize) -> Result<usize> {
+    datastream
+        .windows(size)
         .enumerate()
         .find(|(_, w)| w.iter().collect::<HashSet<&char>>().len() == w.len())
-        .map(|(i, _)| i + 14)
-        .expect("index");
-    println!("{part2}");
+        .map(|(i, _)| i + size)
+        .ok_or_else(|| anyhow!("no {size} characters in a row are all different"))
+}
+
+const EXAMPLE: &str = "bvwbjplbgvbhsrlpgdmjqwftvncz";
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test1() {
+        let result = part1(&parse_input(EXAMPLE).unwrap()).unwrap();
+        assert_eq!(result, 5);
+    }
+
+    #[test]
+    fn test2() {
+        let result = part2(&parse_input(EXAMPLE).unwrap()).unwrap();
+        assert_eq!(result, 23);
+    }
 
-    Ok(())
-}
\ No newline at end of file
+    #[test]
+    fn test_errors() {
+        assert!(parse_input("").is_err());
+        let error = parse_input("abC").unwrap_err().downcast::<crate::parse::ParseError>().unwrap();
+        assert_eq!((error.line, error.column), (1, 3));
+        assert!(part1(&parse_input("x").unwrap()).is_err());
+        assert!(part1(&parse_input("abab").unwrap()).is_err());
+    }
+}
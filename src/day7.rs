@@ -1,5 +1,6 @@
 use anyhow::Result;
-use std::fs;
+use crate::Solution;
+use crate::parse::{lines, Line, ParseError};
 use std::slice::Iter;
 use std::collections::LinkedList;
 
@@ -10,17 +11,31 @@ struct File {
 }
 
 #[derive(Debug)]
-struct Directory {
+pub struct Directory {
     files: LinkedList<File>,
     dirs: LinkedList<Directory>,
 }
 
-fn main() -> Result<()> {
-    let root: Directory = parse_file_system(fs::read_to_string("day7.input")?.as_str());
-    let size_of_directories: LinkedList<u32> = get_directory_size(&root);
-    println!("{}", part1(&size_of_directories));
-    println!("{}", part2(&size_of_directories));
-    Ok(())
+pub struct Day7;
+
+impl Solution for Day7 {
+    const DAY: u8 = 7;
+    const EXAMPLE: Option<&'static str> = Some(EXAMPLE);
+    type Input = Directory;
+    type Part1 = u32;
+    type Part2 = u32;
+
+    fn parse(input: &str) -> Result<Self::Input> {
+        parse_file_system(input)
+    }
+
+    fn part1(root: &Self::Input) -> Result<Self::Part1> {
+        Ok(part1(&get_directory_size(root)))
+    }
+
+    fn part2(root: &Self::Input) -> Result<Self::Part2> {
+        Ok(part2(&get_directory_size(root)))
+    }
 }
 
 fn part1(size_of_directories: &LinkedList<u32>) -> u32 {
@@ -36,58 +51,57 @@ fn part2(size_of_directories: &LinkedList<u32>) -> u32 {
 
 #[derive(Debug)]
 enum Command {
-    ChangeDirectory(String),
+    ChangeDirectory(#[allow(dead_code)] String),
     Parent,
     Root,
     List,
 }
 
-fn parse_file_system(terminal_output: &str) -> Directory {
-    parse_line(&mut terminal_output.split("\n").collect::<Vec<&str>>().iter())
+fn parse_file_system(terminal_output: &str) -> Result<Directory> {
+    Ok(parse_line(&mut lines(terminal_output).iter())?)
 }
 
-fn parse_line(cursor: &mut Iter<'_, &str>) -> Directory {
+fn parse_line(cursor: &mut Iter<'_, Line>) -> Result<Directory, ParseError> {
     let mut files: LinkedList<File> = LinkedList::new();
     let mut dirs: LinkedList<Directory> = LinkedList::new();
     while let Some(line) = cursor.next() {
-        if line.starts_with("$") {
-            match parse_command(line.split_once(" ").unwrap().1) {
+        if let Some(command) = line.text.strip_prefix("$ ") {
+            match parse_command(line, command)? {
                 Command::Root => continue,
                 Command::Parent => break,
-                Command::ChangeDirectory(_) => dirs.push_back(parse_line(cursor)),
+                Command::ChangeDirectory(_) => dirs.push_back(parse_line(cursor)?),
                 Command::List => continue,
             }
         } else {
-            match parse_file(line) {
+            match parse_file(line)? {
                 Some(file) => files.push_back(file),
                 None => continue,
             }
         }
     }
-    Directory {files: files, dirs: dirs}
+    Ok(Directory {files, dirs})
 }
 
-fn parse_command(command: &str) -> Command {
+fn parse_command(line: &Line, command: &str) -> Result<Command, ParseError> {
     match command.split_once(" ") {
-        Some(("cd", "/")) => Command::Root,
-        Some(("cd", "..")) => Command::Parent,
-        Some(("cd", dir)) => Command::ChangeDirectory(dir.to_string()),
-        None => Command::List,
-        Some((_, _)) => panic!("unrecorgnized command {command}")
+        Some(("cd", "/")) => Ok(Command::Root),
+        Some(("cd", "..")) => Ok(Command::Parent),
+        Some(("cd", dir)) => Ok(Command::ChangeDirectory(dir.to_string())),
+        None if command == "ls" => Ok(Command::List),
+        _ => Err(line.error(command, "\"cd <dir>\" or \"ls\"")),
     }
 }
 
-fn parse_file(list_line: &str) -> Option<File> {
-    match list_line.split_once(" ") {
-        Some(("dir", _)) => None,
-        Some((size, name)) => Some(File {_name: name.to_string(), size: size.parse::<u32>().unwrap()}),
-        _ => panic!("unrecorgnized list result {list_line}")
+fn parse_file(line: &Line) -> Result<Option<File>, ParseError> {
+    match line.split_once(" ")? {
+        ("dir", _) => Ok(None),
+        (size, name) => Ok(Some(File {_name: name.to_string(), size: line.parse::<u32>(size, "a file size")?})),
     }
 }
 
 fn get_directory_size(current: &Directory) -> LinkedList<u32> {
     let size_of_files: u32 = current.files.iter().map(|file| file.size).sum();
-    let directories: Vec<LinkedList<u32>> = current.dirs.iter().map(|dir| get_directory_size(dir)).collect();
+    let directories: Vec<LinkedList<u32>> = current.dirs.iter().map(get_directory_size).collect();
     let size_of_directories: u32 = directories.iter().filter_map(|result| result.front()).sum();
     let mut result: LinkedList<u32> = LinkedList::new();
     result.push_back(size_of_files + size_of_directories); // put the size of the current directory at the front
@@ -97,11 +111,7 @@ fn get_directory_size(current: &Directory) -> LinkedList<u32> {
     result
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-
-    const TERMINAL_OUTPUT: &str = r#"$ cd /
+const EXAMPLE: &str = r#"$ cd /
 $ ls
 dir a
 14848514 b.txt
@@ -125,15 +135,30 @@ $ ls
 5626152 d.ext
 7214296 k"#;
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+
     #[test]
     fn test1() {
-        let result: u32 = part1(&get_directory_size(&parse_file_system(TERMINAL_OUTPUT)));
+        let result: u32 = part1(&get_directory_size(&parse_file_system(EXAMPLE).unwrap()));
         assert_eq!(result, 95437);
     }
 
     #[test]
     fn test2() {
-        let result: u32 = part2(&get_directory_size(&parse_file_system(TERMINAL_OUTPUT)));
+        let result: u32 = part2(&get_directory_size(&parse_file_system(EXAMPLE).unwrap()));
         assert_eq!(result, 24933642);
     }
+
+    #[test]
+    fn test_errors() {
+        let error = |input: &str| parse_file_system(input).unwrap_err().downcast::<ParseError>().unwrap();
+        let command = error("$ cd /\n$ mv a b");
+        assert_eq!((command.line, command.column, command.token.as_str()), (2, 3, "mv a b"));
+        let size = error("$ cd /\n$ ls\nabc d.txt");
+        assert_eq!((size.line, size.column, size.token.as_str()), (3, 1, "abc"));
+        let entry = error("$ ls\n1234");
+        assert_eq!((entry.line, entry.token.as_str()), (2, "1234"));
+    }
 }
\ No newline at end of file
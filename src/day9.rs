@@ -1,72 +1,62 @@
 use anyhow::Result;
-use std::fs;
-use std::iter;
+use crate::Solution;
+use crate::geometry::{Direction, Point};
+use crate::parse::{lines, Line, ParseError};
 use std::collections::LinkedList;
 use std::collections::HashSet;
 
-#[derive(PartialEq, Eq, Hash, Copy, Clone)]
 #[derive(Debug)]
-struct Position {
-    x: i32,
-    y: i32,
+struct State {
+    head: Point,
+    tails: LinkedList<Point>,
 }
 
-impl Position {
-    fn of(x: i32, y: i32) -> Position {
-        Position{ x, y }
-    }
+pub struct Day9;
 
-    fn origin() -> Position {
-        Position::of(0, 0)
-    }
+impl Solution for Day9 {
+    const DAY: u8 = 9;
+    const EXAMPLE: Option<&'static str> = Some(EXAMPLE);
+    type Input = Vec<Direction>;
+    type Part1 = usize;
+    type Part2 = usize;
 
-    fn add(&self, another: &Position) -> Position {
-        Position::of(self.x + another.x, self.y + another.y)
+    fn parse(input: &str) -> Result<Self::Input> {
+        parse_input(input)
     }
-}
-
-#[derive(Debug)]
-struct State {
-    head: Position,
-    tails: LinkedList<Position>,
-}
 
-#[derive(Clone)]
-#[derive(Debug)]
-enum Direction {
-    Right,
-    Left,
-    Up,
-    Down,
-}
+    fn part1(directions: &Self::Input) -> Result<Self::Part1> {
+        Ok(part1(directions))
+    }
 
-fn main() -> Result<()> {
-    let directions: Vec<Direction> = parse_input(fs::read_to_string("day9.input")?.as_str());
-    println!("{}", part1(&directions));
-    println!("{}", part2(&directions));
-    Ok(())
+    fn part2(directions: &Self::Input) -> Result<Self::Part2> {
+        Ok(part2(directions))
+    }
 }
 
-fn parse_input(input: &str) -> Vec<Direction> {
-    input.split("\n")
-      .filter_map(|line| line.split_once(" "))
-      .flat_map(|(dir, times)| iter::repeat(parse_direction(dir)).take(times.parse::<usize>().unwrap()))
-      .collect()
+fn parse_input(input: &str) -> Result<Vec<Direction>> {
+    let mut directions: Vec<Direction> = Vec::new();
+    for line in lines(input) {
+        let (dir, times) = line.split_once(" ")?;
+        let direction: Direction = parse_direction(&line, dir)?;
+        let times: usize = line.parse::<usize>(times, "a step count")?;
+        directions.extend(std::iter::repeat_n(direction, times));
+    }
+    Ok(directions)
 }
 
-fn parse_direction(dir: &str) -> Direction {
+fn parse_direction(line: &Line, dir: &str) -> Result<Direction, ParseError> {
     match dir {
-        "R" => Direction::Right,
-        "L" => Direction::Left,
-        "U" => Direction::Up,
-        "D" => Direction::Down,
-        d => panic!("unrecognized direction {d}")
+        "R" => Ok(Direction::Right),
+        "L" => Ok(Direction::Left),
+        "U" => Ok(Direction::Up),
+        "D" => Ok(Direction::Down),
+        d => Err(line.error(d, "one of R, L, U, D")),
     }
 }
 
 fn moves(state: &State, direction: &Direction) -> State {
-    let new_head: Position = move_head(&state.head, direction);
-    let new_tails: LinkedList<Position> = state.tails
+    let new_head: Point = move_head(&state.head, direction);
+    let new_tails: LinkedList<Point> = state.tails
       .iter()
       .fold(LinkedList::from([new_head]), |mut acc, tail| {acc.push_back(move_tail(acc.back().unwrap(), tail)); acc})
       .into_iter()
@@ -75,75 +65,69 @@ fn moves(state: &State, direction: &Direction) -> State {
     State {head: new_head, tails: new_tails}
 }
 
-fn move_head(head: &Position, direction: &Direction) -> Position {
-    let offset = match direction {
-        Direction::Right => Position::of(1, 0),
-        Direction::Left => Position::of(-1, 0),
-        Direction::Up => Position::of(0, 1),
-        Direction::Down => Position::of(0, -1),
-    };
-    head.add(&offset)
+fn move_head(head: &Point, direction: &Direction) -> Point {
+    *head + direction.step()
 }
 
-fn move_tail(head: &Position, tail: &Position) -> Position {
-    let offset = match (head.x - tail.x, head.y - tail.y) {
-        (0, 0) | (0, 1) | (1, 0) | (0, -1) | (-1, 0) | (1, 1) | (1, -1) | (-1, 1) | (-1, -1) => Position::of(0, 0),
-        (2, 0) => Position::of(1, 0),
-        (0, 2) => Position::of(0, 1),
-        (-2, 0) => Position::of(-1, 0),
-        (0, -2) => Position::of(0, -1),
-        (2, 1) | (1, 2) | (2, 2) => Position::of(1, 1),
-        (2, -1) | (1, -2) | (2, -2) => Position::of(1, -1),
-        (-2, 1) | (-1, 2) | (-2, 2) => Position::of(-1, 1),
-        (-2, -1) | (-1, -2) | (-2, -2) => Position::of(-1, -1),
-        (x, y) => panic!("head tail distance ({x},{y})")
-    };
-    tail.add(&offset)
+fn move_tail(head: &Point, tail: &Point) -> Point {
+    if head.chebyshev(tail) > 1 {
+        *tail + (*head - *tail).signum()
+    } else {
+        *tail
+    }
 }
 
-fn part1(directions: &Vec<Direction>) -> usize {
-    let identity = LinkedList::from([State { head: Position::origin(), tails: LinkedList::from([Position::origin()]) }]);
+fn part1(directions: &[Direction]) -> usize {
+    let identity = LinkedList::from([State { head: Point::ORIGIN, tails: LinkedList::from([Point::ORIGIN]) }]);
     directions.iter()
       .fold(identity, |mut acc, direction| {acc.push_back(moves(acc.back().unwrap(), direction)); acc})
       .iter()
-      .map(|state| state.tails.back().unwrap().clone())
-      .collect::<HashSet<Position>>()
+      .map(|state| *state.tails.back().unwrap())
+      .collect::<HashSet<Point>>()
       .len()
 }
 
-fn part2(directions: &Vec<Direction>) -> usize {
-    let identity = LinkedList::from([State { head: Position::origin(), tails: LinkedList::from([Position::origin(); 9]) }]);
+fn part2(directions: &[Direction]) -> usize {
+    let identity = LinkedList::from([State { head: Point::ORIGIN, tails: LinkedList::from([Point::ORIGIN; 9]) }]);
     directions.iter()
       .fold(identity, |mut acc, direction| {acc.push_back(moves(acc.back().unwrap(), direction)); acc})
       .iter()
-      .map(|state| state.tails.back().unwrap().clone())
-      .collect::<HashSet<Position>>()
+      .map(|state| *state.tails.back().unwrap())
+      .collect::<HashSet<Point>>()
       .len()
 }
 
 
+const EXAMPLE: &str = "R 4\n\
+                       U 4\n\
+                       L 3\n\
+                       D 1\n\
+                       R 4\n\
+                       D 1\n\
+                       L 5\n\
+                       R 2";
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
-    const INPUT: &str = "R 4\n\
-                         U 4\n\
-                         L 3\n\
-                         D 1\n\
-                         R 4\n\
-                         D 1\n\
-                         L 5\n\
-                         R 2";
-
     #[test]
     fn test1() {
-        let result: usize = part1(&parse_input(INPUT));
+        let result: usize = part1(&parse_input(EXAMPLE).unwrap());
         assert_eq!(result, 13);
     }
 
     #[test]
     fn test2() {
-        let result: usize = part2(&parse_input(INPUT));
+        let result: usize = part2(&parse_input(EXAMPLE).unwrap());
         assert_eq!(result, 1);
     }
+
+    #[test]
+    fn test_parse_error() {
+        let error = parse_input("R 4\nX 2").unwrap_err().downcast::<ParseError>().unwrap();
+        assert_eq!((error.line, error.column, error.token.as_str()), (2, 1, "X"));
+        let error = parse_input("R 4\nU four").unwrap_err().downcast::<ParseError>().unwrap();
+        assert_eq!((error.line, error.column, error.token.as_str()), (2, 3, "four"));
+    }
 }
\ No newline at end of file
@@ -0,0 +1,303 @@
+use std::ops::{Add, AddAssign, Mul, Neg, Sub, SubAssign};
+
+/// A point on the integer plane; `y` grows downwards, as in the puzzle maps.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct Point {
+    pub x: i32,
+    pub y: i32,
+}
+
+impl Point {
+    pub const ORIGIN: Point = Point { x: 0, y: 0 };
+
+    pub const fn new(x: i32, y: i32) -> Point {
+        Point { x, y }
+    }
+
+    pub fn manhattan(&self, other: &Point) -> i32 {
+        (self.x - other.x).abs() + (self.y - other.y).abs()
+    }
+
+    pub fn chebyshev(&self, other: &Point) -> i32 {
+        (self.x - other.x).abs().max((self.y - other.y).abs())
+    }
+
+    /// The point with each coordinate replaced by its sign, a step of at most one in every axis.
+    pub fn signum(&self) -> Point {
+        Point::new(self.x.signum(), self.y.signum())
+    }
+
+    pub fn neighbours4(&self) -> impl Iterator<Item = Point> + '_ {
+        Direction::ALL.iter().map(move |direction| *self + direction.step())
+    }
+
+    pub fn neighbours8(&self) -> impl Iterator<Item = Point> + '_ {
+        (-1..=1).flat_map(move |y| (-1..=1).map(move |x| Point::new(x, y)))
+            .filter(|step| *step != Point::ORIGIN)
+            .map(move |step| *self + step)
+    }
+}
+
+/// A point in integer space.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct Point3 {
+    pub x: i32,
+    pub y: i32,
+    pub z: i32,
+}
+
+impl Point3 {
+    pub const ORIGIN: Point3 = Point3 { x: 0, y: 0, z: 0 };
+
+    pub const fn new(x: i32, y: i32, z: i32) -> Point3 {
+        Point3 { x, y, z }
+    }
+
+    pub fn manhattan(&self, other: &Point3) -> i32 {
+        (self.x - other.x).abs() + (self.y - other.y).abs() + (self.z - other.z).abs()
+    }
+
+    pub fn chebyshev(&self, other: &Point3) -> i32 {
+        (self.x - other.x).abs().max((self.y - other.y).abs()).max((self.z - other.z).abs())
+    }
+
+    /// The six points sharing a face with this one.
+    pub fn neighbours6(&self) -> [Point3; 6] {
+        [
+            *self + Point3::new(1, 0, 0),
+            *self + Point3::new(-1, 0, 0),
+            *self + Point3::new(0, 1, 0),
+            *self + Point3::new(0, -1, 0),
+            *self + Point3::new(0, 0, 1),
+            *self + Point3::new(0, 0, -1),
+        ]
+    }
+}
+
+macro_rules! impl_operators {
+    ($point:ident { $($axis:ident),+ }) => {
+        impl Add for $point {
+            type Output = $point;
+
+            fn add(self, other: $point) -> $point {
+                $point { $($axis: self.$axis + other.$axis),+ }
+            }
+        }
+
+        impl Sub for $point {
+            type Output = $point;
+
+            fn sub(self, other: $point) -> $point {
+                $point { $($axis: self.$axis - other.$axis),+ }
+            }
+        }
+
+        impl Neg for $point {
+            type Output = $point;
+
+            fn neg(self) -> $point {
+                $point { $($axis: -self.$axis),+ }
+            }
+        }
+
+        impl Mul<i32> for $point {
+            type Output = $point;
+
+            fn mul(self, factor: i32) -> $point {
+                $point { $($axis: self.$axis * factor),+ }
+            }
+        }
+
+        impl AddAssign for $point {
+            fn add_assign(&mut self, other: $point) {
+                *self = *self + other;
+            }
+        }
+
+        impl SubAssign for $point {
+            fn sub_assign(&mut self, other: $point) {
+                *self = *self - other;
+            }
+        }
+
+        impl Coordinates for $point {
+            const ONE: $point = $point { $($axis: 1),+ };
+
+            fn lower(self, other: $point) -> $point {
+                $point { $($axis: self.$axis.min(other.$axis)),+ }
+            }
+
+            fn upper(self, other: $point) -> $point {
+                $point { $($axis: self.$axis.max(other.$axis)),+ }
+            }
+
+            fn all_le(self, other: $point) -> bool {
+                $(self.$axis <= other.$axis)&&+
+            }
+        }
+    };
+}
+
+impl_operators!(Point { x, y });
+impl_operators!(Point3 { x, y, z });
+
+/// Componentwise comparisons shared by [`Point`] and [`Point3`].
+pub trait Coordinates: Copy + Add<Output = Self> + Sub<Output = Self> {
+    const ONE: Self;
+
+    fn lower(self, other: Self) -> Self;
+
+    fn upper(self, other: Self) -> Self;
+
+    fn all_le(self, other: Self) -> bool;
+}
+
+/// The smallest box holding a set of points, with both corners inclusive.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Bounds<P> {
+    pub min: P,
+    pub max: P,
+}
+
+impl<P: Coordinates> Bounds<P> {
+    pub fn of(points: impl IntoIterator<Item = P>) -> Option<Bounds<P>> {
+        let mut points = points.into_iter();
+        let first = points.next()?;
+        let mut bounds = Bounds { min: first, max: first };
+        points.for_each(|point| bounds.include(point));
+        Some(bounds)
+    }
+
+    pub fn include(&mut self, point: P) {
+        self.min = self.min.lower(point);
+        self.max = self.max.upper(point);
+    }
+
+    pub fn contains(&self, point: P) -> bool {
+        self.min.all_le(point) && point.all_le(self.max)
+    }
+
+    /// The number of points along each axis.
+    pub fn size(&self) -> P {
+        self.max - self.min + P::ONE
+    }
+}
+
+/// One of the four orthogonal headings on a map.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Direction {
+    Up,
+    Right,
+    Down,
+    Left,
+}
+
+impl Direction {
+    /// All directions, clockwise from up.
+    pub const ALL: [Direction; 4] = [Direction::Up, Direction::Right, Direction::Down, Direction::Left];
+
+    pub fn step(&self) -> Point {
+        match self {
+            Direction::Up => Point::new(0, -1),
+            Direction::Right => Point::new(1, 0),
+            Direction::Down => Point::new(0, 1),
+            Direction::Left => Point::new(-1, 0),
+        }
+    }
+
+    /// The step as a `(row, column)` offset for [`crate::grid::Grid::offset`].
+    pub fn offset(&self) -> (isize, isize) {
+        let step = self.step();
+        (step.y as isize, step.x as isize)
+    }
+
+    pub fn rotate(&self, rotation: Rotation) -> Direction {
+        Direction::ALL[(self.index() + rotation.quarter_turns()) % 4]
+    }
+
+    pub fn turn_right(&self) -> Direction {
+        self.rotate(Rotation::Clockwise)
+    }
+
+    pub fn turn_left(&self) -> Direction {
+        self.rotate(Rotation::CounterClockwise)
+    }
+
+    pub fn opposite(&self) -> Direction {
+        self.rotate(Rotation::Upsidedown)
+    }
+
+    fn index(&self) -> usize {
+        Direction::ALL.iter().position(|direction| direction == self).unwrap()
+    }
+}
+
+/// A turn by a whole number of quarter turns.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Rotation {
+    None,
+    Clockwise,
+    Upsidedown,
+    CounterClockwise,
+}
+
+impl Rotation {
+    const ALL: [Rotation; 4] = [Rotation::None, Rotation::Clockwise, Rotation::Upsidedown, Rotation::CounterClockwise];
+
+    /// The rotation turning `from` into `to`.
+    pub fn between(from: Direction, to: Direction) -> Rotation {
+        Rotation::ALL[(to.index() + 4 - from.index()) % 4]
+    }
+
+    pub fn reverse(&self) -> Rotation {
+        Rotation::ALL[(4 - self.quarter_turns()) % 4]
+    }
+
+    fn quarter_turns(&self) -> usize {
+        Rotation::ALL.iter().position(|rotation| rotation == self).unwrap()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_distances() {
+        let a = Point::new(2, 18);
+        let b = Point::new(-2, 15);
+        assert_eq!(a.manhattan(&b), 7);
+        assert_eq!(a.chebyshev(&b), 4);
+        assert_eq!((a - b) * 2 + -b, Point::new(10, -9));
+        assert_eq!(Point3::new(1, 2, 3).manhattan(&Point3::ORIGIN), 6);
+        assert_eq!(Point3::new(1, -5, 3).chebyshev(&Point3::ORIGIN), 5);
+    }
+
+    #[test]
+    fn test_directions() {
+        assert_eq!(Direction::Up.turn_right(), Direction::Right);
+        assert_eq!(Direction::Left.turn_right(), Direction::Up);
+        assert_eq!(Direction::Up.turn_left(), Direction::Left);
+        assert_eq!(Direction::Down.opposite(), Direction::Up);
+        for from in Direction::ALL {
+            for to in Direction::ALL {
+                let rotation = Rotation::between(from, to);
+                assert_eq!(from.rotate(rotation), to);
+                assert_eq!(to.rotate(rotation.reverse()), from);
+            }
+        }
+        assert_eq!(Direction::Left.offset(), (0, -1));
+        assert_eq!(Point::ORIGIN.neighbours4().map(|p| p.manhattan(&Point::ORIGIN)).sum::<i32>(), 4);
+        assert_eq!(Point::ORIGIN.neighbours8().filter(|p| p.chebyshev(&Point::ORIGIN) == 1).count(), 8);
+    }
+
+    #[test]
+    fn test_bounds() {
+        let bounds = Bounds::of([Point3::new(1, 5, 2), Point3::new(3, 2, 2), Point3::new(2, 4, 6)]).unwrap();
+        assert_eq!(bounds, Bounds { min: Point3::new(1, 2, 2), max: Point3::new(3, 5, 6) });
+        assert_eq!(bounds.size(), Point3::new(3, 4, 5));
+        assert!(bounds.contains(Point3::new(2, 2, 6)));
+        assert!(!bounds.contains(Point3::new(2, 1, 6)));
+        assert_eq!(Bounds::<Point>::of([]), None);
+    }
+}
@@ -0,0 +1,218 @@
+use crate::parse::{lines, Line, ParseError};
+use std::ops::{Index, IndexMut};
+
+/// A `(row, column)` cell of a grid.
+pub type Position = (usize, usize);
+
+/// Row and column steps to the four orthogonal neighbours: up, down, left, right.
+pub const ORTHOGONAL: [(isize, isize); 4] = [(-1, 0), (1, 0), (0, -1), (0, 1)];
+
+/// Row and column steps to all eight surrounding cells, clockwise from the top left.
+pub const SURROUNDING: [(isize, isize); 8] = [(-1, -1), (-1, 0), (-1, 1), (0, 1), (1, 1), (1, 0), (1, -1), (0, -1)];
+
+/// A rectangular grid of cells stored row by row.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Grid<T> {
+    cells: Vec<T>,
+    rows: usize,
+    columns: usize,
+}
+
+impl<T: Clone> Grid<T> {
+    pub fn new(rows: usize, columns: usize, value: T) -> Grid<T> {
+        Grid { cells: vec![value; rows * columns], rows, columns }
+    }
+
+    pub fn transpose(&self) -> Grid<T> {
+        Grid::from_fn(self.columns, self.rows, |(row, column)| self[(column, row)].clone())
+    }
+
+    /// The `rows` by `columns` part of the grid whose top left cell is `origin`.
+    pub fn crop(&self, origin: Position, rows: usize, columns: usize) -> Grid<T> {
+        Grid::from_fn(rows, columns, |(row, column)| self[(origin.0 + row, origin.1 + column)].clone())
+    }
+
+    /// Parses a character map, one row per line, turning each character into a cell with `cell`.
+    pub fn parse(input: &str, cell: impl FnMut(char) -> Option<T>, expected: &str) -> Result<Grid<T>, ParseError> {
+        Grid::from_lines(&lines(input), cell, expected)
+    }
+
+    /// Builds a grid from lines of equal length; characters `cell` rejects are reported as not `expected`.
+    pub fn from_lines(lines: &[Line], cell: impl FnMut(char) -> Option<T>, expected: &str) -> Result<Grid<T>, ParseError> {
+        let columns = lines.first().map(|line| line.text.chars().count()).unwrap_or(0);
+        Grid::collect(lines, columns, None, cell, expected)
+    }
+
+    fn collect(lines: &[Line], columns: usize, padding: Option<T>, mut cell: impl FnMut(char) -> Option<T>, expected: &str) -> Result<Grid<T>, ParseError> {
+        let mut cells = Vec::with_capacity(lines.len() * columns);
+        for line in lines {
+            let mut count = 0;
+            for (column, c) in line.chars() {
+                if column > columns {
+                    return Err(line.error_at(column, &c.to_string(), &format!("a row of {columns} cells")));
+                }
+                cells.push(cell(c).ok_or_else(|| line.error_at(column, &c.to_string(), expected))?);
+                count = column;
+            }
+            match &padding {
+                Some(padding) => cells.extend(std::iter::repeat_n(padding.clone(), columns - count)),
+                None if count < columns => return Err(line.error_at(count + 1, "", &format!("a row of {columns} cells"))),
+                None => {},
+            }
+        }
+        Ok(Grid { cells, rows: lines.len(), columns })
+    }
+
+    /// Like [`Grid::from_lines`], but short rows are filled up with `padding`.
+    pub fn from_lines_padded(lines: &[Line], padding: T, cell: impl FnMut(char) -> Option<T>, expected: &str) -> Result<Grid<T>, ParseError> {
+        let columns = lines.iter().map(|line| line.text.chars().count()).max().unwrap_or(0);
+        Grid::collect(lines, columns, Some(padding), cell, expected)
+    }
+}
+
+impl<T> Grid<T> {
+    pub fn from_fn(rows: usize, columns: usize, mut cell: impl FnMut(Position) -> T) -> Grid<T> {
+        let cells = (0..rows * columns).map(|index| cell((index / columns, index % columns))).collect();
+        Grid { cells, rows, columns }
+    }
+
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    pub fn columns(&self) -> usize {
+        self.columns
+    }
+
+    pub fn contains(&self, position: Position) -> bool {
+        position.0 < self.rows && position.1 < self.columns
+    }
+
+    pub fn get(&self, position: Position) -> Option<&T> {
+        if self.contains(position) { Some(&self[position]) } else { None }
+    }
+
+    pub fn get_mut(&mut self, position: Position) -> Option<&mut T> {
+        if self.contains(position) { Some(&mut self[position]) } else { None }
+    }
+
+    /// The cell `step` away from `position`, if it lies inside the grid.
+    pub fn offset(&self, position: Position, step: (isize, isize)) -> Option<Position> {
+        let row = position.0.checked_add_signed(step.0)?;
+        let column = position.1.checked_add_signed(step.1)?;
+        if self.contains((row, column)) { Some((row, column)) } else { None }
+    }
+
+    pub fn neighbours4(&self, position: Position) -> impl Iterator<Item = Position> + '_ {
+        ORTHOGONAL.iter().filter_map(move |step| self.offset(position, *step))
+    }
+
+    pub fn neighbours8(&self, position: Position) -> impl Iterator<Item = Position> + '_ {
+        SURROUNDING.iter().filter_map(move |step| self.offset(position, *step))
+    }
+
+    pub fn row(&self, row: usize) -> &[T] {
+        &self.cells[row * self.columns..(row + 1) * self.columns]
+    }
+
+    pub fn column(&self, column: usize) -> impl DoubleEndedIterator<Item = &T> + ExactSizeIterator {
+        self.cells.iter().skip(column).step_by(self.columns.max(1)).take(self.rows)
+    }
+
+    /// Appends a row at the bottom; it must be as wide as the grid.
+    pub fn push_row(&mut self, row: Vec<T>) {
+        assert!(self.rows == 0 || row.len() == self.columns, "row of {} cells pushed onto a grid of {} columns", row.len(), self.columns);
+        self.columns = row.len();
+        self.cells.extend(row);
+        self.rows += 1;
+    }
+
+    pub fn positions(&self) -> impl Iterator<Item = Position> {
+        let columns = self.columns;
+        (0..self.rows * columns).map(move |index| (index / columns, index % columns))
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (Position, &T)> {
+        self.positions().zip(self.cells.iter())
+    }
+
+    pub fn map<U>(&self, f: impl FnMut(&T) -> U) -> Grid<U> {
+        Grid { cells: self.cells.iter().map(f).collect(), rows: self.rows, columns: self.columns }
+    }
+
+    /// Draws the grid with one character per cell and a newline after every row.
+    pub fn render(&self, mut f: impl FnMut(&T) -> char) -> String {
+        let mut text = String::with_capacity(self.rows * (self.columns + 1));
+        for row in 0..self.rows {
+            text.extend(self.row(row).iter().map(&mut f));
+            text.push('\n');
+        }
+        text
+    }
+}
+
+impl<T> Index<Position> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, (row, column): Position) -> &T {
+        assert!(column < self.columns, "column {column} out of {} columns", self.columns);
+        &self.cells[row * self.columns + column]
+    }
+}
+
+impl<T> IndexMut<Position> for Grid<T> {
+    fn index_mut(&mut self, (row, column): Position) -> &mut T {
+        assert!(column < self.columns, "column {column} out of {} columns", self.columns);
+        &mut self.cells[row * self.columns + column]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn digits(input: &str) -> Result<Grid<u32>, ParseError> {
+        Grid::parse(input, |c| c.to_digit(10), "a digit")
+    }
+
+    #[test]
+    fn test_parse() {
+        let grid = digits("123\n456").unwrap();
+        assert_eq!((grid.rows(), grid.columns()), (2, 3));
+        assert_eq!(grid[(1, 2)], 6);
+        assert_eq!(grid.get((2, 0)), None);
+        assert_eq!(grid.row(1), &[4, 5, 6]);
+        assert_eq!(grid.column(1).copied().collect::<Vec<u32>>(), vec![2, 5]);
+
+        let error = digits("123\n4x6").unwrap_err();
+        assert_eq!((error.line, error.column, error.token.as_str()), (2, 2, "x"));
+        let error = digits("123\n45").unwrap_err();
+        assert_eq!((error.line, error.column), (2, 3));
+        let error = digits("123\n4567").unwrap_err();
+        assert_eq!((error.line, error.column, error.token.as_str()), (2, 4, "7"));
+    }
+
+    #[test]
+    fn test_padded() {
+        let grid = Grid::from_lines_padded(&lines("  #\n#"), ' ', Some, "").unwrap();
+        assert_eq!(grid.render(|c| *c), "  #\n#  \n");
+    }
+
+    #[test]
+    fn test_neighbours() {
+        let grid = Grid::new(3, 3, 0);
+        assert_eq!(grid.neighbours4((0, 0)).collect::<Vec<Position>>(), vec![(1, 0), (0, 1)]);
+        assert_eq!(grid.neighbours4((1, 1)).count(), 4);
+        assert_eq!(grid.neighbours8((0, 2)).collect::<Vec<Position>>(), vec![(1, 2), (1, 1), (0, 1)]);
+        assert_eq!(grid.neighbours8((1, 1)).count(), 8);
+    }
+
+    #[test]
+    fn test_transpose_and_render() {
+        let grid = digits("123\n456").unwrap();
+        let transposed = grid.transpose();
+        assert_eq!(transposed.render(|d| char::from_digit(*d, 10).unwrap()), "14\n25\n36\n");
+        assert_eq!(transposed.transpose(), grid);
+        assert_eq!(grid.crop((0, 1), 2, 2).render(|d| char::from_digit(*d, 10).unwrap()), "23\n56\n");
+    }
+}
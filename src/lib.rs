@@ -0,0 +1,182 @@
+use anyhow::{bail, Result};
+use bench::Timings;
+use std::fmt::Display;
+
+pub mod answers;
+pub mod bench;
+pub mod geometry;
+pub mod grid;
+pub mod parse;
+pub mod search;
+
+pub mod day1;
+pub mod day2;
+pub mod day3;
+pub mod day4;
+pub mod day5;
+pub mod day6;
+pub mod day7;
+pub mod day8;
+pub mod day9;
+pub mod day10;
+pub mod day11;
+pub mod day12;
+pub mod day13;
+pub mod day14;
+pub mod day15;
+pub mod day16;
+pub mod day17;
+pub mod day18;
+pub mod day19;
+pub mod day20;
+pub mod day21;
+pub mod day22;
+pub mod day23;
+pub mod day24;
+pub mod day25;
+
+/// A day of the calendar: how to parse its input and how to answer each part.
+///
+/// ```
+/// use aoc2022::Solution;
+/// use aoc2022::day25::Day25;
+///
+/// let numbers = Day25::parse("1=-0-2\n12111")?;
+/// assert_eq!(Day25::part1(&numbers)?, "1-111=");
+/// # Ok::<(), anyhow::Error>(())
+/// ```
+pub trait Solution {
+    const DAY: u8;
+    const PARTS: u8 = 2;
+    /// The sample input given in the puzzle text, if the day has one.
+    const EXAMPLE: Option<&'static str> = None;
+
+    type Input;
+    type Part1: Display;
+    type Part2: Display;
+
+    fn parse(input: &str) -> Result<Self::Input>;
+
+    /// Parses the sample input. Days whose example is asked about with other
+    /// parameters than the real puzzle override this.
+    fn example() -> Result<Self::Input> {
+        match Self::EXAMPLE {
+            Some(example) => Self::parse(example),
+            None => bail!("day {} has no example", Self::DAY),
+        }
+    }
+
+    fn part1(input: &Self::Input) -> Result<Self::Part1>;
+    fn part2(input: &Self::Input) -> Result<Self::Part2>;
+}
+
+/// Where the text handed to a solver comes from.
+#[derive(Clone, Copy)]
+pub enum Input<'a> {
+    Text(&'a str),
+    Example,
+}
+
+/// Parses `input` and renders the answer to one part of `S`.
+pub fn run<S: Solution>(input: Input, part: u8) -> Result<String> {
+    let parsed = match input {
+        Input::Text(text) => S::parse(text)?,
+        Input::Example => S::example()?,
+    };
+    match part {
+        1 => Ok(S::part1(&parsed)?.to_string()),
+        2 if S::PARTS > 1 => Ok(S::part2(&parsed)?.to_string()),
+        _ => bail!("day {} has no part {part}", S::DAY),
+    }
+}
+
+/// A solved day of the calendar, as seen by the `aoc` runner.
+pub struct Puzzle {
+    pub day: u8,
+    pub parts: u8,
+    pub example: bool,
+    pub run: fn(Input, u8) -> Result<String>,
+    pub bench: fn(Input, &[u8], usize) -> Result<Timings>,
+}
+
+impl Puzzle {
+    const fn of<S: Solution>() -> Puzzle {
+        Puzzle { day: S::DAY, parts: S::PARTS, example: S::EXAMPLE.is_some(), run: run::<S>, bench: bench::measure::<S> }
+    }
+}
+
+pub const PUZZLES: [Puzzle; 25] = [
+    Puzzle::of::<day1::Day1>(),
+    Puzzle::of::<day2::Day2>(),
+    Puzzle::of::<day3::Day3>(),
+    Puzzle::of::<day4::Day4>(),
+    Puzzle::of::<day5::Day5>(),
+    Puzzle::of::<day6::Day6>(),
+    Puzzle::of::<day7::Day7>(),
+    Puzzle::of::<day8::Day8>(),
+    Puzzle::of::<day9::Day9>(),
+    Puzzle::of::<day10::Day10>(),
+    Puzzle::of::<day11::Day11>(),
+    Puzzle::of::<day12::Day12>(),
+    Puzzle::of::<day13::Day13>(),
+    Puzzle::of::<day14::Day14>(),
+    Puzzle::of::<day15::Day15>(),
+    Puzzle::of::<day16::Day16>(),
+    Puzzle::of::<day17::Day17>(),
+    Puzzle::of::<day18::Day18>(),
+    Puzzle::of::<day19::Day19>(),
+    Puzzle::of::<day20::Day20>(),
+    Puzzle::of::<day21::Day21>(),
+    Puzzle::of::<day22::Day22>(),
+    Puzzle::of::<day23::Day23>(),
+    Puzzle::of::<day24::Day24>(),
+    Puzzle::of::<day25::Day25>(),
+];
+
+pub fn puzzle(day: u8) -> Option<&'static Puzzle> {
+    PUZZLES.iter().find(|puzzle| puzzle.day == day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_line_endings<S: Solution>() {
+        let example = S::EXAMPLE.unwrap();
+        let crlf = example.replace('\n', "\r\n") + "\r\n\r\n";
+        let trailing = format!("{example}\n\n");
+        for part in 1..=S::PARTS {
+            let expected = run::<S>(Input::Text(example), part).unwrap();
+            assert_eq!(run::<S>(Input::Text(&crlf), part).unwrap(), expected, "day {} part {part}", S::DAY);
+            assert_eq!(run::<S>(Input::Text(&trailing), part).unwrap(), expected, "day {} part {part}", S::DAY);
+        }
+    }
+
+    #[test]
+    fn test_line_endings() {
+        assert_line_endings::<day1::Day1>();
+        assert_line_endings::<day2::Day2>();
+        assert_line_endings::<day3::Day3>();
+        assert_line_endings::<day4::Day4>();
+        assert_line_endings::<day5::Day5>();
+        assert_line_endings::<day6::Day6>();
+        assert_line_endings::<day7::Day7>();
+        assert_line_endings::<day8::Day8>();
+        assert_line_endings::<day9::Day9>();
+        assert_line_endings::<day10::Day10>();
+        assert_line_endings::<day11::Day11>();
+        assert_line_endings::<day12::Day12>();
+        assert_line_endings::<day13::Day13>();
+        assert_line_endings::<day14::Day14>();
+        assert_line_endings::<day16::Day16>();
+        assert_line_endings::<day17::Day17>();
+        assert_line_endings::<day18::Day18>();
+        assert_line_endings::<day19::Day19>();
+        assert_line_endings::<day20::Day20>();
+        assert_line_endings::<day21::Day21>();
+        assert_line_endings::<day22::Day22>();
+        assert_line_endings::<day23::Day23>();
+        assert_line_endings::<day24::Day24>();
+        assert_line_endings::<day25::Day25>();
+    }
+}
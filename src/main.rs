@@ -0,0 +1,357 @@
+use anyhow::{anyhow, bail, Context, Result};
+use aoc2022::answers::Answers;
+use aoc2022::bench::{Samples, Timings};
+use aoc2022::{puzzle, Input, Puzzle, PUZZLES};
+use serde_json::json;
+use std::env;
+use std::fs;
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+const USAGE: &str = "usage:
+    aoc list
+    aoc run <day|all> [--format <text|json>] [--part <1|2>] [--input <path|->] [--example] [--input-dir <dir>]
+    aoc bench <day|all> [--repeat <n>] [--format <text|json>] [--part <1|2>] [--input <path|->] [--example] [--input-dir <dir>]
+    aoc verify <day|all> [--answers <path>] [--record] [--part <1|2>] [--input <path|->] [--example] [--input-dir <dir>]
+
+Without --input, day N reads dayN.input from --input-dir, $AOC_INPUT_DIR or the crate root;
+days without an input file or without the part asked for are skipped when running all,
+except that verify fails a day without an input file if it has recorded answers.
+--format json prints one JSON object per line: {day, part, answer, duration} for run, where
+duration is in seconds and includes parsing, and per-phase nanosecond statistics for bench.
+bench times the parse phase and each part separately, --repeat times (default 10).
+verify compares answers with --answers (default answers.txt next to the inputs, which holds
+the real answers, so --example needs its own file); --record stores them instead.";
+
+enum Source {
+    File(PathBuf),
+    Stdin,
+    Example,
+    Directory(PathBuf),
+}
+
+enum Format {
+    Text,
+    Json,
+}
+
+enum Command {
+    List,
+    Run { days: Vec<&'static Puzzle>, part: Option<u8>, source: Source, format: Format },
+    Bench { days: Vec<&'static Puzzle>, part: Option<u8>, source: Source, repeat: usize, format: Format },
+    Verify { days: Vec<&'static Puzzle>, part: Option<u8>, source: Source, answers: PathBuf, record: bool },
+}
+
+fn main() -> Result<()> {
+    let args: Vec<String> = env::args().skip(1).collect();
+    match parse_args(&args).with_context(|| USAGE)? {
+        Command::List => list(),
+        Command::Run { days, part, source, format } => run(&days, part, &source, &format),
+        Command::Bench { days, part, source, repeat, format } => bench(&days, part, &source, repeat, &format),
+        Command::Verify { days, part, source, answers, record } => verify(&days, part, &source, &answers, record),
+    }
+}
+
+fn parse_args(args: &[String]) -> Result<Command> {
+    let mut iter = args.iter();
+    match iter.next().map(|s| s.as_str()) {
+        Some("list") => Ok(Command::List),
+        Some(command @ ("run" | "bench" | "verify")) => {
+            let days: Vec<&'static Puzzle> = match iter.next().map(|s| s.as_str()) {
+                Some("all") => PUZZLES.iter().collect(),
+                Some(day) => {
+                    let number = day.parse::<u8>().with_context(|| format!("invalid day {day}"))?;
+                    vec![puzzle(number).ok_or_else(|| anyhow!("day {number} is not solved"))?]
+                },
+                None => bail!("missing day"),
+            };
+            let mut part = None;
+            let mut input = None;
+            let mut example = false;
+            let mut input_dir = None;
+            let mut repeat = None;
+            let mut format = None;
+            let mut answers = None;
+            let mut record = false;
+            while let Some(flag) = iter.next() {
+                if flag == "--example" {
+                    example = true;
+                    continue;
+                }
+                if flag == "--record" && command == "verify" {
+                    record = true;
+                    continue;
+                }
+                let value = iter.next().ok_or_else(|| anyhow!("missing value for {flag}"))?;
+                match flag.as_str() {
+                    "--part" => part = Some(value.parse::<u8>().with_context(|| format!("invalid part {value}"))?),
+                    "--input" => input = Some(value.clone()),
+                    "--input-dir" => input_dir = Some(PathBuf::from(value)),
+                    "--repeat" if command == "bench" => repeat = Some(value.parse::<usize>().with_context(|| format!("invalid repeat count {value}"))?),
+                    "--format" if command != "verify" => format = Some(match value.as_str() {
+                        "text" => Format::Text,
+                        "json" => Format::Json,
+                        _ => bail!("unknown format {value}"),
+                    }),
+                    "--answers" if command == "verify" => answers = Some(PathBuf::from(value)),
+                    _ => bail!("unknown option {flag}"),
+                }
+            }
+            if command == "verify" && example && answers.is_none() {
+                bail!("verify --example needs an --answers file of its own");
+            }
+            let input_dir = input_dir.unwrap_or_else(default_input_dir);
+            let answers = answers.unwrap_or_else(|| input_dir.join("answers.txt"));
+            let source = match (input, example) {
+                (Some(_), true) => bail!("--input and --example are exclusive"),
+                (Some(_), false) if days.len() > 1 => bail!("--input needs a single day"),
+                (Some(path), false) if path == "-" => Source::Stdin,
+                (Some(path), false) => Source::File(PathBuf::from(path)),
+                (None, true) => Source::Example,
+                (None, false) => Source::Directory(input_dir),
+            };
+            match command {
+                "run" => return Ok(Command::Run { days, part, source, format: format.unwrap_or(Format::Text) }),
+                "verify" => return Ok(Command::Verify { days, part, source, answers, record }),
+                _ => {},
+            }
+            let repeat = repeat.unwrap_or(10);
+            if repeat == 0 {
+                bail!("--repeat must be at least 1");
+            }
+            Ok(Command::Bench { days, part, source, repeat, format: format.unwrap_or(Format::Text) })
+        },
+        Some(command) => bail!("unknown command {command}"),
+        None => bail!("missing command"),
+    }
+}
+
+fn default_input_dir() -> PathBuf {
+    env::var_os("AOC_INPUT_DIR")
+        .map(PathBuf::from)
+        .unwrap_or_else(|| PathBuf::from(env!("CARGO_MANIFEST_DIR")))
+}
+
+fn list() -> Result<()> {
+    for puzzle in &PUZZLES {
+        println!("day{} ({} part{}{})",
+            puzzle.day,
+            puzzle.parts,
+            if puzzle.parts > 1 { "s" } else { "" },
+            if puzzle.example { ", example" } else { "" });
+    }
+    Ok(())
+}
+
+fn read_input(puzzle: &Puzzle, source: &Source) -> Result<Option<String>> {
+    let path = match source {
+        Source::Example => return Ok(None),
+        Source::Stdin => {
+            let mut text = String::new();
+            io::stdin().read_to_string(&mut text).context("reading stdin")?;
+            return Ok(Some(text));
+        },
+        Source::File(path) => path.clone(),
+        Source::Directory(dir) => dir.join(format!("day{}.input", puzzle.day)),
+    };
+    fs::read_to_string(&path)
+        .with_context(|| format!("reading {}", path.display()))
+        .map(Some)
+}
+
+/// Whether `puzzle` can be run from `source`. When running several days, those without an
+/// example or an input file are skipped; a lone day without an example is an error.
+fn runnable(puzzle: &Puzzle, source: &Source, days: usize) -> Result<bool> {
+    match source {
+        Source::Example if !puzzle.example => {
+            if days > 1 {
+                return Ok(false);
+            }
+            bail!("day {} has no example", puzzle.day);
+        },
+        Source::Directory(dir) if days > 1 => Ok(dir.join(format!("day{}.input", puzzle.day)).exists()),
+        _ => Ok(true),
+    }
+}
+
+/// The parts of `puzzle` to run. When running several days, those without the requested part
+/// run none; a lone day without it is an error.
+fn parts(puzzle: &Puzzle, part: Option<u8>, days: usize) -> Result<Vec<u8>> {
+    match part {
+        Some(part) if part == 0 || part > puzzle.parts => {
+            if days > 1 {
+                return Ok(Vec::new());
+            }
+            bail!("day {} has no part {part}", puzzle.day)
+        },
+        Some(part) => Ok(vec![part]),
+        None => Ok((1..=puzzle.parts).collect()),
+    }
+}
+
+fn run(days: &[&Puzzle], part: Option<u8>, source: &Source, format: &Format) -> Result<()> {
+    for puzzle in days {
+        if !runnable(puzzle, source, days.len())? {
+            continue;
+        }
+        let parts: Vec<u8> = parts(puzzle, part, days.len())?;
+        if parts.is_empty() {
+            continue;
+        }
+        let text = read_input(puzzle, source)?;
+        if days.len() > 1 && matches!(format, Format::Text) {
+            println!("day{}", puzzle.day);
+        }
+        for part in parts {
+            let input = match &text {
+                Some(text) => Input::Text(text),
+                None => Input::Example,
+            };
+            let start = Instant::now();
+            let answer = (puzzle.run)(input, part)?;
+            let duration = start.elapsed();
+            match format {
+                Format::Text => println!("{answer}"),
+                Format::Json => println!("{}", json!({
+                    "day": puzzle.day,
+                    "part": part,
+                    "answer": answer,
+                    "duration": duration.as_secs_f64(),
+                })),
+            }
+        }
+    }
+    Ok(())
+}
+
+fn bench(days: &[&Puzzle], part: Option<u8>, source: &Source, repeat: usize, format: &Format) -> Result<()> {
+    let mut timings: Vec<Timings> = Vec::new();
+    for puzzle in days {
+        if !runnable(puzzle, source, days.len())? {
+            continue;
+        }
+        let parts = parts(puzzle, part, days.len())?;
+        if parts.is_empty() {
+            continue;
+        }
+        let text = read_input(puzzle, source)?;
+        let input = match &text {
+            Some(text) => Input::Text(text),
+            None => Input::Example,
+        };
+        let result = (puzzle.bench)(input, &parts, repeat)?;
+        if let Format::Json = format {
+            print_json(&result, repeat);
+        }
+        timings.push(result);
+    }
+    if let Format::Text = format {
+        print_table(&timings, repeat);
+    }
+    Ok(())
+}
+
+fn print_json(timings: &Timings, repeat: usize) {
+    for samples in &timings.phases {
+        println!("{}", json!({
+            "day": timings.day,
+            "phase": samples.phase,
+            "repeat": repeat,
+            "min_ns": samples.min().as_nanos() as u64,
+            "median_ns": samples.median().as_nanos() as u64,
+            "max_ns": samples.max().as_nanos() as u64,
+        }));
+    }
+}
+
+fn print_table(timings: &[Timings], repeat: usize) {
+    println!("{:<6}{:<8}{:>12}{:>12}{:>12}", "day", "phase", "min", "median", "max");
+    let mut total = Duration::ZERO;
+    for day in timings {
+        for samples in &day.phases {
+            println!("{:<6}{:<8}{:>12}{:>12}{:>12}",
+                day.day,
+                samples.phase,
+                format_duration(samples.min()),
+                format_duration(samples.median()),
+                format_duration(samples.max()));
+        }
+        total += day.phases.iter().map(Samples::median).sum::<Duration>();
+    }
+    if timings.len() > 1 {
+        println!("{:<14}{:>24}", "total", format_duration(total));
+    }
+    println!("{repeat} repetition{}", if repeat > 1 { "s" } else { "" });
+}
+
+fn format_duration(duration: Duration) -> String {
+    format!("{duration:.3?}")
+}
+
+fn verify(days: &[&Puzzle], part: Option<u8>, source: &Source, path: &Path, record: bool) -> Result<()> {
+    let mut answers = match fs::read_to_string(path) {
+        Ok(text) => Answers::parse(&text).with_context(|| format!("reading {}", path.display()))?,
+        Err(_) if record => Answers::default(),
+        Err(error) => return Err(error).with_context(|| format!("reading {}", path.display())),
+    };
+    let (mut passed, mut failed, mut missing) = (0, 0, 0);
+    for puzzle in days {
+        let parts = parts(puzzle, part, days.len())?;
+        if !runnable(puzzle, source, days.len())? {
+            // a day skipped for want of an input file still fails if it has answers to check
+            if let (Source::Directory(dir), false) = (source, record) {
+                for part in &parts {
+                    let label = format!("day{} part{part}", puzzle.day);
+                    if answers.get(puzzle.day, *part).is_some() {
+                        failed += 1;
+                        println!("{label} FAIL: no input file {}", dir.join(format!("day{}.input", puzzle.day)).display());
+                    } else {
+                        missing += 1;
+                        println!("{label} missing, no input file");
+                    }
+                }
+            }
+            continue;
+        }
+        let text = read_input(puzzle, source)?;
+        for part in parts {
+            let input = match &text {
+                Some(text) => Input::Text(text),
+                None => Input::Example,
+            };
+            let label = format!("day{} part{part}", puzzle.day);
+            match ((puzzle.run)(input, part), answers.get(puzzle.day, part)) {
+                (Err(error), _) => {
+                    failed += 1;
+                    println!("{label} FAIL: {error:#}");
+                },
+                (Ok(answer), _) if record => {
+                    println!("{label} recorded {answer:?}");
+                    answers.insert(puzzle.day, part, answer);
+                },
+                (Ok(answer), Some(expected)) if answer == expected => {
+                    passed += 1;
+                    println!("{label} pass");
+                },
+                (Ok(answer), Some(expected)) => {
+                    failed += 1;
+                    println!("{label} FAIL: expected {expected:?}, found {answer:?}");
+                },
+                (Ok(answer), None) => {
+                    missing += 1;
+                    println!("{label} missing, found {answer:?}");
+                },
+            }
+        }
+    }
+    if record {
+        fs::write(path, answers.to_string()).with_context(|| format!("writing {}", path.display()))?;
+    } else {
+        println!("{passed} passed, {failed} failed, {missing} missing");
+    }
+    if failed > 0 {
+        bail!("{failed} answer{} failed", if failed > 1 { "s" } else { "" });
+    }
+    Ok(())
+}
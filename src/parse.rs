@@ -0,0 +1,116 @@
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+
+/// Malformed puzzle input, located by 1-based line and column.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParseError {
+    pub line: usize,
+    pub column: usize,
+    pub token: String,
+    pub expected: String,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}, column {}: expected {}, found {:?}", self.line, self.column, self.expected, self.token)
+    }
+}
+
+impl Error for ParseError {}
+
+/// One line of puzzle input together with its line number.
+#[derive(Debug, Clone, Copy)]
+pub struct Line<'a> {
+    pub number: usize,
+    pub text: &'a str,
+}
+
+impl<'a> Line<'a> {
+    /// Reports `token`, which should be a slice of this line, as not being `expected`.
+    pub fn error(&self, token: &str, expected: &str) -> ParseError {
+        self.error_at(self.column_of(token), token, expected)
+    }
+
+    pub fn error_at(&self, column: usize, token: &str, expected: &str) -> ParseError {
+        ParseError { line: self.number, column, token: token.to_string(), expected: expected.to_string() }
+    }
+
+    pub fn parse<T: FromStr>(&self, token: &str, expected: &str) -> Result<T, ParseError> {
+        token.parse::<T>().map_err(|_| self.error(token, expected))
+    }
+
+    pub fn split_once(&self, delimiter: &str) -> Result<(&'a str, &'a str), ParseError> {
+        self.text.split_once(delimiter).ok_or_else(|| self.error(self.text, &format!("{delimiter:?}")))
+    }
+
+    pub fn strip_prefix(&self, prefix: &str) -> Result<&'a str, ParseError> {
+        self.text.strip_prefix(prefix).ok_or_else(|| self.error(self.text, &format!("{prefix:?}")))
+    }
+
+    /// The characters of the line with their 1-based columns.
+    pub fn chars(&self) -> impl Iterator<Item = (usize, char)> + 'a {
+        self.text.chars().enumerate().map(|(index, c)| (index + 1, c))
+    }
+
+    fn column_of(&self, token: &str) -> usize {
+        let start = self.text.as_ptr() as usize;
+        let offset = (token.as_ptr() as usize).wrapping_sub(start);
+        if offset <= self.text.len() {
+            self.text[..offset].chars().count() + 1
+        } else {
+            1
+        }
+    }
+}
+
+/// Splits the input into lines, accepting `\n` or `\r\n` endings and ignoring trailing blank lines.
+pub fn lines(input: &str) -> Vec<Line<'_>> {
+    let mut lines: Vec<Line> = input.split('\n')
+        .enumerate()
+        .map(|(index, text)| Line { number: index + 1, text: text.strip_suffix('\r').unwrap_or(text) })
+        .collect();
+    while lines.last().is_some_and(|line| line.text.is_empty()) {
+        lines.pop();
+    }
+    lines
+}
+
+/// Splits the input into the non-empty groups of lines between blank lines.
+pub fn blocks(input: &str) -> Vec<Vec<Line<'_>>> {
+    lines(input)
+        .split(|line| line.text.is_empty())
+        .filter(|block| !block.is_empty())
+        .map(|block| block.to_vec())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_error_column() {
+        let line = Line { number: 3, text: "move x from 2" };
+        let token = &line.text[5..6];
+        let error = line.parse::<u32>(token, "a number").unwrap_err();
+        assert_eq!(error, ParseError { line: 3, column: 6, token: "x".to_string(), expected: "a number".to_string() });
+        assert_eq!(error.to_string(), "line 3, column 6: expected a number, found \"x\"");
+    }
+
+    #[test]
+    fn test_blocks() {
+        let blocks = blocks("a\nb\n\nc");
+        assert_eq!(blocks.len(), 2);
+        assert_eq!(blocks[1][0].number, 4);
+        assert_eq!(blocks[1][0].text, "c");
+    }
+
+    #[test]
+    fn test_line_endings() {
+        let lines = lines("a\r\nb\n\r\nc\r\n\r\n\n");
+        let texts: Vec<&str> = lines.iter().map(|line| line.text).collect();
+        assert_eq!(texts, vec!["a", "b", "", "c"]);
+        assert_eq!(lines[3].number, 4);
+    }
+}
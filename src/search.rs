@@ -0,0 +1,214 @@
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashMap, HashSet, VecDeque};
+use std::hash::Hash;
+use std::ops::Add;
+
+/// What a search found: the cost of every state it reached and how it got there.
+#[derive(Debug, Clone)]
+pub struct Search<S, C> {
+    order: Vec<S>,
+    costs: HashMap<S, C>,
+    predecessors: HashMap<S, S>,
+    goal: Option<S>,
+}
+
+impl<S: Clone + Eq + Hash, C: Copy> Search<S, C> {
+    fn new() -> Search<S, C> {
+        Search { order: Vec::new(), costs: HashMap::new(), predecessors: HashMap::new(), goal: None }
+    }
+
+    /// The first goal state settled, if the search reached one.
+    pub fn goal(&self) -> Option<&S> {
+        self.goal.as_ref()
+    }
+
+    /// The cost of the goal state.
+    pub fn goal_cost(&self) -> Option<C> {
+        self.goal.as_ref().and_then(|goal| self.cost(goal))
+    }
+
+    /// The cheapest known cost of reaching `state`; final for every settled state.
+    pub fn cost(&self, state: &S) -> Option<C> {
+        self.costs.get(state).copied()
+    }
+
+    pub fn contains(&self, state: &S) -> bool {
+        self.costs.contains_key(state)
+    }
+
+    /// The state `state` was reached from; start states have none.
+    pub fn predecessor(&self, state: &S) -> Option<&S> {
+        self.predecessors.get(state)
+    }
+
+    /// The states in the order they were settled, starting with the start states.
+    pub fn settled(&self) -> &[S] {
+        &self.order
+    }
+
+    /// The states from a start state up to and including `state`.
+    pub fn path(&self, state: &S) -> Option<Vec<S>> {
+        if !self.contains(state) {
+            return None;
+        }
+        let mut path = vec![state.clone()];
+        while let Some(previous) = self.predecessor(path.last().unwrap()) {
+            path.push(previous.clone());
+        }
+        path.reverse();
+        Some(path)
+    }
+}
+
+/// Breadth-first search from every state in `starts` at once, stopping at the first state
+/// `goal` accepts. The cost of a state is the number of steps from the nearest start.
+pub fn bfs<S, I>(starts: impl IntoIterator<Item = S>, mut neighbours: impl FnMut(&S) -> I, mut goal: impl FnMut(&S) -> bool) -> Search<S, usize>
+where
+    S: Clone + Eq + Hash,
+    I: IntoIterator<Item = S>,
+{
+    let mut search = Search::new();
+    let mut queue = VecDeque::new();
+    for start in starts {
+        if !search.contains(&start) {
+            search.costs.insert(start.clone(), 0);
+            queue.push_back(start);
+        }
+    }
+    while let Some(state) = queue.pop_front() {
+        search.order.push(state.clone());
+        if goal(&state) {
+            search.goal = Some(state);
+            break;
+        }
+        let cost = search.costs[&state] + 1;
+        for next in neighbours(&state) {
+            if !search.contains(&next) {
+                search.costs.insert(next.clone(), cost);
+                search.predecessors.insert(next.clone(), state.clone());
+                queue.push_back(next);
+            }
+        }
+    }
+    search
+}
+
+/// Cheapest-first search where `neighbours` yields each next state with the cost of the step.
+pub fn dijkstra<S, C, I>(starts: impl IntoIterator<Item = S>, neighbours: impl FnMut(&S) -> I, goal: impl FnMut(&S) -> bool) -> Search<S, C>
+where
+    S: Clone + Eq + Hash,
+    C: Copy + Ord + Add<Output = C> + Default,
+    I: IntoIterator<Item = (S, C)>,
+{
+    astar(starts, neighbours, |_| C::default(), goal)
+}
+
+/// Like [`dijkstra`], but guided by `heuristic`, which must never overestimate the remaining
+/// cost to a goal and must not drop by more than the cost of any step.
+pub fn astar<S, C, I>(starts: impl IntoIterator<Item = S>, mut neighbours: impl FnMut(&S) -> I, mut heuristic: impl FnMut(&S) -> C, mut goal: impl FnMut(&S) -> bool) -> Search<S, C>
+where
+    S: Clone + Eq + Hash,
+    C: Copy + Ord + Add<Output = C> + Default,
+    I: IntoIterator<Item = (S, C)>,
+{
+    let mut search = Search::new();
+    let mut heap = BinaryHeap::new();
+    for start in starts {
+        if !search.contains(&start) {
+            search.costs.insert(start.clone(), C::default());
+            heap.push(Entry { priority: heuristic(&start), cost: C::default(), state: start });
+        }
+    }
+    let mut settled = HashSet::new();
+    while let Some(Entry { state, cost, .. }) = heap.pop() {
+        if cost > search.costs[&state] || !settled.insert(state.clone()) {
+            continue;
+        }
+        search.order.push(state.clone());
+        if goal(&state) {
+            search.goal = Some(state);
+            break;
+        }
+        for (next, step) in neighbours(&state) {
+            let next_cost = cost + step;
+            if search.cost(&next).is_none_or(|known| next_cost < known) {
+                search.costs.insert(next.clone(), next_cost);
+                search.predecessors.insert(next.clone(), state.clone());
+                heap.push(Entry { priority: next_cost + heuristic(&next), cost: next_cost, state: next });
+            }
+        }
+    }
+    search
+}
+
+/// A queued state, ordered so that the heap pops the lowest priority first.
+struct Entry<S, C> {
+    priority: C,
+    cost: C,
+    state: S,
+}
+
+impl<S, C: Ord> PartialEq for Entry<S, C> {
+    fn eq(&self, other: &Self) -> bool {
+        self.priority == other.priority
+    }
+}
+
+impl<S, C: Ord> Eq for Entry<S, C> {}
+
+impl<S, C: Ord> PartialOrd for Entry<S, C> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<S, C: Ord> Ord for Entry<S, C> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.priority.cmp(&self.priority)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::geometry::Point;
+
+    // a 5 by 5 room with a wall from (2, 0) down to (2, 3)
+    fn open(point: &Point) -> bool {
+        (0..5).contains(&point.x) && (0..5).contains(&point.y) && !(point.x == 2 && point.y < 4)
+    }
+
+    #[test]
+    fn test_bfs() {
+        let goal = Point::new(4, 0);
+        let search = bfs([Point::ORIGIN], |point| point.neighbours4().filter(open).collect::<Vec<Point>>(), |point| *point == goal);
+        assert_eq!(search.goal_cost(), Some(12));
+        let path = search.path(&goal).unwrap();
+        assert_eq!(path.len(), 13);
+        assert_eq!((path[0], path[12]), (Point::ORIGIN, goal));
+        assert!(path.windows(2).all(|step| step[0].manhattan(&step[1]) == 1 && open(&step[1])));
+
+        let search = bfs([Point::ORIGIN, Point::new(4, 4)], |point| point.neighbours4().filter(open).collect::<Vec<Point>>(), |_| false);
+        assert_eq!(search.goal(), None);
+        assert_eq!(search.settled().len(), 21);
+        assert_eq!(search.cost(&goal), Some(4));
+        assert_eq!(search.path(&goal).unwrap()[0], Point::new(4, 4));
+    }
+
+    #[test]
+    fn test_weighted() {
+        // climbing costs the height difference plus one
+        let height = |point: &Point| if point.y == 2 { 5 } else { 0 };
+        let neighbours = |point: &Point| point.neighbours4()
+            .filter(|next| (0..5).contains(&next.x) && (0..5).contains(&next.y))
+            .map(|next| (next, 1 + (height(&next) - height(point)).max(0)))
+            .collect::<Vec<(Point, i32)>>();
+        let goal = Point::new(4, 4);
+        let search = dijkstra([Point::ORIGIN], neighbours, |point| *point == goal);
+        assert_eq!(search.goal_cost(), Some(13));
+        let guided = astar([Point::ORIGIN], neighbours, |point| point.manhattan(&goal), |point| *point == goal);
+        assert_eq!(guided.goal_cost(), Some(13));
+        assert!(guided.settled().len() <= search.settled().len());
+        assert_eq!(guided.path(&goal).unwrap().len(), 9);
+    }
+}